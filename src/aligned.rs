@@ -0,0 +1,163 @@
+use super::{AllocErr, Allocator, Global, LayoutErr};
+use core::{alloc::Layout, ptr::NonNull};
+
+/// An allocator wrapper which raises the alignment of every request to a
+/// minimum alignment, such as `64` for cache lines and AVX-512 loads or `4096`
+/// for pages. Since the alignment is stored in the allocator, allocations
+/// backed by it (e.g. `RawVec<T, Aligned>`) remember it, and resizing and
+/// deallocating use the matching layout. Zero-sized allocations still use a
+/// dangling pointer aligned only for the element type.
+///
+/// ```rust
+/// use tux_owned_alloc::{Aligned, OwnedAlloc, RawVec};
+///
+/// let mut vec = RawVec::<f32, _>::with_capacity_aligned(100, 64);
+/// assert_eq!(vec.raw().as_ptr() as usize % 64, 0);
+///
+/// vec.resize(1000);
+/// assert_eq!(vec.raw().as_ptr() as usize % 64, 0);
+///
+/// let page = OwnedAlloc::new_in([0u8; 512], Aligned::new(4096));
+/// assert_eq!(page.raw().as_ptr() as *const u8 as usize % 4096, 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aligned<A = Global>
+where
+    A: Allocator,
+{
+    inner: A,
+    align: usize,
+}
+
+impl Aligned {
+    /// Creates a wrapper of the global allocator with the given minimum
+    /// alignment.
+    ///
+    /// # Panics
+    /// Panics if the alignment is not a power of two.
+    pub fn new(align: usize) -> Self {
+        Self::wrap(Global, align)
+    }
+
+    /// Creates a wrapper of the global allocator with the given minimum
+    /// alignment. If the alignment is not a power of two, `Err` is returned.
+    pub fn try_new(align: usize) -> Result<Self, LayoutErr> {
+        Self::try_wrap(Global, align)
+    }
+}
+
+impl<A> Aligned<A>
+where
+    A: Allocator,
+{
+    /// Creates a wrapper of the given allocator with the given minimum
+    /// alignment.
+    ///
+    /// # Panics
+    /// Panics if the alignment is not a power of two.
+    pub fn wrap(inner: A, align: usize) -> Self {
+        match Self::try_wrap(inner, align) {
+            Ok(this) => this,
+            Err(err) => panic!("Invalid alignment {}: {}", align, err),
+        }
+    }
+
+    /// Creates a wrapper of the given allocator with the given minimum
+    /// alignment. If the alignment is not a power of two, `Err` is returned.
+    pub fn try_wrap(inner: A, align: usize) -> Result<Self, LayoutErr> {
+        if align.is_power_of_two() {
+            Ok(Self { inner, align })
+        } else {
+            Err(LayoutErr)
+        }
+    }
+
+    /// Returns a reference to the wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// The minimum alignment of the requests.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
+    fn raise(&self, layout: Layout) -> Result<Layout, AllocErr> {
+        layout.align_to(self.align).map_err(|_| AllocErr { layout })
+    }
+}
+
+unsafe impl<A> Allocator for Aligned<A>
+where
+    A: Allocator,
+{
+    fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        self.inner.alloc(self.raise(layout)?)
+    }
+
+    fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        self.inner.alloc_zeroed(self.raise(layout)?)
+    }
+
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+        let layout = layout.align_to(self.align).unwrap_unchecked();
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        let layout = layout.align_to(self.align).unwrap_unchecked();
+        // The caller only checked `new_size` against the original alignment.
+        Layout::from_size_align(new_size, layout.align())
+            .map_err(|_| AllocErr { layout })?;
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        super::{OwnedAlloc, RawVec, UninitAlloc},
+        Aligned,
+    };
+
+    #[test]
+    fn invalid_alignment() {
+        assert!(Aligned::try_new(48).is_err());
+        assert!(RawVec::<u8, _>::try_with_capacity_aligned(8, 0).is_err());
+    }
+
+    #[test]
+    fn raw_vec_keeps_alignment() {
+        let mut vec = RawVec::<u8, _>::with_capacity_aligned(3, 256);
+        for cap in [1, 300, 4, 5000] {
+            vec.resize(cap);
+            assert_eq!(vec.raw().as_ptr() as usize % 256, 0);
+            assert_eq!(vec.allocator().align(), 256);
+        }
+    }
+
+    #[test]
+    fn resize_overflowing_raised_alignment() {
+        let mut vec = RawVec::<u8, _>::with_capacity_in(1, Aligned::new(4096));
+        assert!(vec.try_resize(isize::MAX as usize - 10).is_err());
+        assert_eq!(vec.cap(), 1);
+        assert_eq!(vec.raw().as_ptr() as usize % 4096, 0);
+    }
+
+    #[test]
+    fn conversions_keep_alignment() {
+        let alloc = UninitAlloc::<u64, _>::new_in(Aligned::new(128));
+        let vec = RawVec::from(alloc);
+        let uninit = UninitAlloc::from(vec);
+        assert_eq!(uninit.raw().as_ptr() as *mut u64 as usize % 128, 0);
+
+        let owned = OwnedAlloc::new_in(7u16, Aligned::new(64));
+        assert_eq!(owned.raw().as_ptr() as usize % 64, 0);
+        assert_eq!(*owned, 7);
+    }
+}
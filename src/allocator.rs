@@ -0,0 +1,172 @@
+use super::AllocErr;
+use alloc::alloc::{alloc, alloc_zeroed, dealloc, realloc};
+use core::{
+    alloc::Layout,
+    ptr::{self, NonNull},
+};
+
+/// A memory allocator which can back the allocations of this crate, such as
+/// `UninitAlloc`, `OwnedAlloc` and `RawVec`. Unlike the unstable standard
+/// library allocator API, this trait is available on stable Rust.
+///
+/// The types of this crate never call these methods with zero-sized layouts;
+/// zero-sized allocations are represented by dangling pointers instead.
+///
+/// # Safety
+/// Implementors must return pointers to blocks of memory valid for reads and
+/// writes of the requested layout, and the blocks must stay valid until they
+/// are deallocated or reallocated through the same allocator (or a copy of
+/// it).
+pub unsafe trait Allocator {
+    /// Allocates a block of memory fitting the given layout. In case of
+    /// allocation error, `Err` is returned.
+    fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr>;
+
+    /// Allocates a block of memory fitting the given layout whose bytes are
+    /// all zero. In case of allocation error, `Err` is returned. The default
+    /// implementation allocates the block and then fills it with zeros.
+    fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        let ptr = self.alloc(layout)?;
+        unsafe { ptr.as_ptr().write_bytes(0, layout.size()) };
+        Ok(ptr)
+    }
+
+    /// Deallocates the block of memory pointed by `ptr`.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because `ptr` must have been allocated by
+    /// this allocator with the given layout, and passing anything else leads
+    /// to undefined behaviour.
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Resizes the block of memory pointed by `ptr` to `new_size`, keeping
+    /// the alignment of `layout`. The contents are preserved up to the smaller
+    /// of the old and new sizes. In case of allocation error, `Err` is
+    /// returned and the original block is untouched. If `new_size`, rounded up
+    /// to the alignment, overflows `isize`, `Err` is returned as well, so
+    /// implementations must check it before building the new layout. The
+    /// default implementation allocates a new block, copies the contents and
+    /// deallocates the old block.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because `ptr` must have been allocated by
+    /// this allocator with the given layout, and passing anything else leads
+    /// to undefined behaviour. `new_size` must not be zero.
+    unsafe fn realloc(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        let new_layout = Layout::from_size_align(new_size, layout.align())
+            .map_err(|_| AllocErr { layout })?;
+        let new_ptr = self.alloc(new_layout)?;
+        ptr::copy_nonoverlapping(
+            ptr.as_ptr(),
+            new_ptr.as_ptr(),
+            layout.size().min(new_size),
+        );
+        self.dealloc(ptr, layout);
+        Ok(new_ptr)
+    }
+}
+
+unsafe impl<A> Allocator for &A
+where
+    A: Allocator + ?Sized,
+{
+    fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        (**self).alloc(layout)
+    }
+
+    fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        (**self).alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        (**self).realloc(ptr, layout, new_size)
+    }
+}
+
+/// The global memory allocator, i.e. the one registered via stdlib. This is
+/// the default allocator of every type in this crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        NonNull::new(unsafe { alloc(layout) }).ok_or(AllocErr { layout })
+    }
+
+    fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        NonNull::new(unsafe { alloc_zeroed(layout) }).ok_or(AllocErr { layout })
+    }
+
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+        dealloc(ptr.as_ptr(), layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        let new_layout = Layout::from_size_align(new_size, layout.align())
+            .map_err(|_| AllocErr { layout })?;
+        NonNull::new(realloc(ptr.as_ptr(), layout, new_size))
+            .ok_or(AllocErr { layout: new_layout })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{super::AllocErr, Allocator, Global};
+    use core::{alloc::Layout, ptr::NonNull};
+
+    /// Forwards to `Global`, but keeps the default `realloc`.
+    struct DefaultRealloc;
+
+    unsafe impl Allocator for DefaultRealloc {
+        fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+            Global.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+            Global.dealloc(ptr, layout)
+        }
+    }
+
+    fn check_realloc_overflow<A>(alloc: A)
+    where
+        A: Allocator,
+    {
+        let layout = Layout::from_size_align(8, 64).unwrap();
+        let ptr = alloc.alloc(layout).unwrap();
+        unsafe {
+            ptr.as_ptr().write(5);
+            let res = alloc.realloc(ptr, layout, isize::MAX as usize - 10);
+            assert_eq!(res.unwrap_err().layout, layout);
+            assert_eq!(ptr.as_ptr().read(), 5);
+
+            let ptr = alloc.realloc(ptr, layout, 100).unwrap();
+            assert_eq!(ptr.as_ptr().read(), 5);
+            alloc.dealloc(ptr, Layout::from_size_align(100, 64).unwrap());
+        }
+    }
+
+    #[test]
+    fn realloc_overflow() {
+        check_realloc_overflow(Global);
+        check_realloc_overflow(DefaultRealloc);
+    }
+}
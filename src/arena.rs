@@ -0,0 +1,208 @@
+use super::{AllocErr, Allocator, OwnedAlloc, RawVec, RawVecErr, UninitAlloc};
+use alloc::vec::Vec;
+use core::{
+    alloc::Layout,
+    cell::{Cell, RefCell},
+    fmt,
+    ptr::NonNull,
+};
+
+/// Default size in bytes of the chunks allocated by an `Arena`.
+const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+/// A bump (region) allocator. Memory is carved from large chunks backed by
+/// `RawVec<u8>`, and all of it is freed at once when the arena is dropped.
+/// Deallocating a single allocation is a no-op. A reference to the arena is
+/// an `Allocator`, so the handles it yields are plain `UninitAlloc` and
+/// `OwnedAlloc` borrowing the arena. Values stored in an `OwnedAlloc` are still
+/// dropped when the handle is dropped.
+///
+/// ```rust
+/// use tux_owned_alloc::{Arena, OwnedAlloc};
+///
+/// let arena = Arena::new();
+/// let first = arena.init(String::from("first"));
+/// let second = OwnedAlloc::new_in(2u64, &arena);
+///
+/// assert_eq!(*first, "first");
+/// assert_eq!(*second, 2);
+///
+/// let (val, uninit) = first.move_inner();
+/// assert_eq!(*uninit.init(val + " again"), "first again");
+/// ```
+pub struct Arena {
+    chunks: RefCell<Vec<RawVec<u8>>>,
+    current: Cell<usize>,
+    offset: Cell<usize>,
+    chunk_size: usize,
+}
+
+impl Arena {
+    /// Creates a new arena with the default chunk size. No allocation is
+    /// performed until the first allocation request.
+    pub fn new() -> Self {
+        Self::with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a new arena whose chunks have (at least) the given size in
+    /// bytes. Requests bigger than the chunk size get a chunk of their own. No
+    /// allocation is performed until the first allocation request.
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            chunks: RefCell::new(Vec::new()),
+            current: Cell::new(0),
+            offset: Cell::new(0),
+            chunk_size,
+        }
+    }
+
+    /// Creates room for a `T` in the arena. In case of allocation error, the
+    /// handler registered via stdlib is called.
+    pub fn uninit<T>(&self) -> UninitAlloc<T, &Self> {
+        UninitAlloc::new_in(self)
+    }
+
+    /// Creates room for a `T` in the arena. In case of allocation error, `Err`
+    /// is returned.
+    pub fn try_uninit<T>(&self) -> Result<UninitAlloc<T, &Self>, AllocErr> {
+        UninitAlloc::try_new_in(self)
+    }
+
+    /// Creates room for a `T` in the arena and initializes it to the passed
+    /// argument. In case of allocation error, the handler registered via stdlib
+    /// is called.
+    pub fn init<T>(&self, val: T) -> OwnedAlloc<T, &Self> {
+        OwnedAlloc::new_in(val, self)
+    }
+
+    /// Creates room for a `T` in the arena and initializes it to the passed
+    /// argument. In case of allocation error, `Err` is returned.
+    pub fn try_init<T>(
+        &self,
+        val: T,
+    ) -> Result<OwnedAlloc<T, &Self>, AllocErr> {
+        OwnedAlloc::try_new_in(val, self)
+    }
+
+    /// The total size in bytes of the chunks owned by the arena.
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(RawVec::cap).sum()
+    }
+
+    /// Makes all the memory of the arena available again, keeping the chunks
+    /// allocated. Since it takes `&mut self`, no handle can be alive.
+    pub fn reset(&mut self) {
+        self.current.set(0);
+        self.offset.set(0);
+    }
+
+    fn bump(
+        chunk: &RawVec<u8>,
+        offset: usize,
+        layout: Layout,
+    ) -> Option<usize> {
+        let addr = chunk.raw().as_ptr() as usize;
+        let start = addr.checked_add(offset)?;
+        let padding = start.wrapping_neg() & (layout.align() - 1);
+        let end = offset.checked_add(padding)?.checked_add(layout.size())?;
+        if end <= chunk.cap() {
+            Some(offset + padding)
+        } else {
+            None
+        }
+    }
+
+    fn new_chunk(&self, layout: Layout) -> Result<RawVec<u8>, AllocErr> {
+        let size = layout
+            .size()
+            .checked_add(layout.align() - 1)
+            .ok_or(AllocErr { layout })?
+            .max(self.chunk_size);
+
+        RawVec::try_with_capacity(size).map_err(|err| match err {
+            RawVecErr::Alloc(err) => err,
+            RawVecErr::Layout(_) => AllocErr { layout },
+        })
+    }
+}
+
+unsafe impl Allocator for Arena {
+    fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        let mut chunks = self.chunks.borrow_mut();
+        let mut current = self.current.get();
+        let mut offset = self.offset.get();
+
+        loop {
+            if current == chunks.len() {
+                chunks.push(self.new_chunk(layout)?);
+            }
+
+            if let Some(start) = Self::bump(&chunks[current], offset, layout) {
+                self.current.set(current);
+                self.offset.set(start + layout.size());
+                let ptr = unsafe { chunks[current].raw().as_ptr().add(start) };
+                break Ok(unsafe { NonNull::new_unchecked(ptr) });
+            }
+
+            current += 1;
+            offset = 0;
+        }
+    }
+
+    unsafe fn dealloc(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Arena {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmtr,
+            "Arena {{ chunks: {}, capacity: {} }}",
+            self.chunks.borrow().len(),
+            self.capacity()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Arena;
+
+    #[test]
+    fn allocations_are_aligned_and_disjoint() {
+        let arena = Arena::with_chunk_size(64);
+        let byte = arena.init(1u8);
+        let wide = arena.init(2u128);
+        let other = arena.init(3u8);
+
+        assert_eq!(wide.raw().as_ptr() as usize % 16, 0);
+        assert_ne!(byte.raw().cast::<u8>(), other.raw().cast::<u8>());
+        assert_eq!((*byte, *wide, *other), (1, 2, 3));
+    }
+
+    #[test]
+    fn big_requests_get_own_chunk() {
+        let arena = Arena::with_chunk_size(16);
+        let big = arena.init([7u64; 32]);
+        assert_eq!(*big, [7; 32]);
+        assert!(arena.capacity() >= 256);
+    }
+
+    #[test]
+    fn reset_reuses_chunks() {
+        let mut arena = Arena::with_chunk_size(64);
+        let first = arena.init(5u32).raw();
+        let cap = arena.capacity();
+
+        arena.reset();
+        let second = arena.init(6u32);
+
+        assert_eq!(first, second.raw());
+        assert_eq!(arena.capacity(), cap);
+    }
+}
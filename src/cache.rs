@@ -3,9 +3,7 @@
 ///
 /// # Dummy Example
 /// ```rust
-/// extern crate owned_alloc;
-///
-/// use owned_alloc::{Cache, RawVec, UninitAlloc};
+/// use tux_owned_alloc::{Cache, RawVec, UninitAlloc};
 ///
 /// fn do_some_stuff(iter: usize, n: usize) -> usize {
 ///     let mut cache = Cache::new();
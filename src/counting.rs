@@ -0,0 +1,223 @@
+use super::{AllocErr, Allocator, Global};
+use core::{
+    alloc::Layout,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+
+/// A snapshot of the statistics recorded by a `CountingAlloc`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Number of successful allocations, zeroed or not.
+    pub allocs: usize,
+    /// Number of deallocations.
+    pub deallocs: usize,
+    /// Number of successful reallocations which kept the block in place.
+    pub reallocs_in_place: usize,
+    /// Number of successful reallocations which moved the block.
+    pub reallocs_moved: usize,
+    /// Number of failed allocations and reallocations.
+    pub failures: usize,
+    /// Bytes currently allocated.
+    pub live_bytes: usize,
+    /// Maximum of `live_bytes` ever reached.
+    pub peak_bytes: usize,
+}
+
+/// An allocator wrapper which records statistics about the requests forwarded
+/// to the inner allocator, useful for tests and metrics. A reference to it is
+/// also an `Allocator`, so a single counter can be shared by many allocations.
+///
+/// ```rust
+/// use tux_owned_alloc::{Cache, CountingAlloc, UninitAlloc};
+///
+/// let counter = CountingAlloc::new();
+/// let mut cache = Cache::new();
+///
+/// for i in 0 .. 100u64 {
+///     let alloc = cache.take_or(|| UninitAlloc::new_in(&counter));
+///     let (_, alloc) = alloc.init(i).move_inner();
+///     cache.store(alloc);
+/// }
+///
+/// assert_eq!(counter.stats().allocs, 1);
+/// assert_eq!(counter.stats().live_bytes, 8);
+/// ```
+#[derive(Debug, Default)]
+pub struct CountingAlloc<A = Global>
+where
+    A: Allocator,
+{
+    inner: A,
+    allocs: AtomicUsize,
+    deallocs: AtomicUsize,
+    reallocs_in_place: AtomicUsize,
+    reallocs_moved: AtomicUsize,
+    failures: AtomicUsize,
+    live_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+}
+
+impl CountingAlloc {
+    /// Creates a counter wrapping the global allocator.
+    pub fn new() -> Self {
+        Self::wrap(Global)
+    }
+}
+
+impl<A> CountingAlloc<A>
+where
+    A: Allocator,
+{
+    /// Creates a counter wrapping the given allocator.
+    pub fn wrap(inner: A) -> Self {
+        Self {
+            inner,
+            allocs: AtomicUsize::new(0),
+            deallocs: AtomicUsize::new(0),
+            reallocs_in_place: AtomicUsize::new(0),
+            reallocs_moved: AtomicUsize::new(0),
+            failures: AtomicUsize::new(0),
+            live_bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns a reference to the wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Returns a snapshot of the statistics recorded so far.
+    pub fn stats(&self) -> AllocStats {
+        AllocStats {
+            allocs: self.allocs.load(Relaxed),
+            deallocs: self.deallocs.load(Relaxed),
+            reallocs_in_place: self.reallocs_in_place.load(Relaxed),
+            reallocs_moved: self.reallocs_moved.load(Relaxed),
+            failures: self.failures.load(Relaxed),
+            live_bytes: self.live_bytes.load(Relaxed),
+            peak_bytes: self.peak_bytes.load(Relaxed),
+        }
+    }
+
+    /// Resets every counter to zero, except `live_bytes`, which still reflects
+    /// the allocations alive. `peak_bytes` is reset to `live_bytes`.
+    pub fn reset(&self) {
+        self.allocs.store(0, Relaxed);
+        self.deallocs.store(0, Relaxed);
+        self.reallocs_in_place.store(0, Relaxed);
+        self.reallocs_moved.store(0, Relaxed);
+        self.failures.store(0, Relaxed);
+        self.peak_bytes.store(self.live_bytes.load(Relaxed), Relaxed);
+    }
+
+    fn grow_live(&self, bytes: usize) {
+        let live = self.live_bytes.fetch_add(bytes, Relaxed) + bytes;
+        self.peak_bytes.fetch_max(live, Relaxed);
+    }
+
+    fn shrink_live(&self, bytes: usize) {
+        self.live_bytes.fetch_sub(bytes, Relaxed);
+    }
+
+    fn count<R>(&self, res: Result<R, AllocErr>) -> Result<R, AllocErr> {
+        if res.is_err() {
+            self.failures.fetch_add(1, Relaxed);
+        }
+        res
+    }
+}
+
+unsafe impl<A> Allocator for CountingAlloc<A>
+where
+    A: Allocator,
+{
+    fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        let ptr = self.count(self.inner.alloc(layout))?;
+        self.allocs.fetch_add(1, Relaxed);
+        self.grow_live(layout.size());
+        Ok(ptr)
+    }
+
+    fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        let ptr = self.count(self.inner.alloc_zeroed(layout))?;
+        self.allocs.fetch_add(1, Relaxed);
+        self.grow_live(layout.size());
+        Ok(ptr)
+    }
+
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.deallocs.fetch_add(1, Relaxed);
+        self.shrink_live(layout.size());
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        let new_ptr = self.count(self.inner.realloc(ptr, layout, new_size))?;
+
+        if new_ptr == ptr {
+            self.reallocs_in_place.fetch_add(1, Relaxed);
+        } else {
+            self.reallocs_moved.fetch_add(1, Relaxed);
+        }
+
+        if new_size >= layout.size() {
+            self.grow_live(new_size - layout.size());
+        } else {
+            self.shrink_live(layout.size() - new_size);
+        }
+
+        Ok(new_ptr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        super::{OwnedAlloc, RawVec},
+        CountingAlloc,
+    };
+
+    #[test]
+    fn counts_owned() {
+        let counter = CountingAlloc::new();
+        let alloc = OwnedAlloc::new_in([0u32; 4], &counter);
+        let stats = counter.stats();
+        assert_eq!((stats.allocs, stats.live_bytes), (1, 16));
+
+        drop(alloc);
+        let stats = counter.stats();
+        assert_eq!(stats.deallocs, 1);
+        assert_eq!(stats.live_bytes, 0);
+        assert_eq!(stats.peak_bytes, 16);
+    }
+
+    #[test]
+    fn counts_raw_vec_resizes() {
+        let counter = CountingAlloc::new();
+        let mut vec = RawVec::<u64, _>::with_capacity_in(4, &counter);
+        vec.resize(64);
+        vec.resize(2);
+
+        let stats = counter.stats();
+        assert_eq!(stats.reallocs_in_place + stats.reallocs_moved, 2);
+        assert_eq!(stats.live_bytes, 16);
+        assert_eq!(stats.peak_bytes, 512);
+
+        drop(vec);
+        assert_eq!(counter.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn zero_sized_is_not_counted() {
+        let counter = CountingAlloc::new();
+        let _alloc = OwnedAlloc::new_in((), &counter);
+        assert_eq!(counter.stats(), Default::default());
+    }
+}
@@ -1,6 +1,8 @@
-use std::{
+use super::{Allocator, Global, OwnedAlloc};
+use core::{
     alloc::{Layout, LayoutError as StdLayoutErr},
     fmt,
+    str::Utf8Error,
 };
 
 /// Error returned from the allocator.
@@ -31,12 +33,18 @@ impl fmt::Display for LayoutErr {
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for AllocErr {}
+
 impl From<StdLayoutErr> for LayoutErr {
     fn from(_err: StdLayoutErr) -> Self {
         LayoutErr
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for LayoutErr {}
+
 /// Errors returned by the `RawVec`.
 #[derive(Debug, Clone)]
 pub enum RawVecErr {
@@ -55,6 +63,16 @@ impl fmt::Display for RawVecErr {
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for RawVecErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RawVecErr::Alloc(err) => Some(err),
+            RawVecErr::Layout(err) => Some(err),
+        }
+    }
+}
+
 impl From<AllocErr> for RawVecErr {
     fn from(err: AllocErr) -> Self {
         RawVecErr::Alloc(err)
@@ -66,3 +84,118 @@ impl From<LayoutErr> for RawVecErr {
         RawVecErr::Layout(err)
     }
 }
+
+/// Errors returned when creating a slice allocation from an iterator.
+#[derive(Debug, Clone)]
+pub enum FromIterErr {
+    /// Allocation error.
+    Alloc(AllocErr),
+    /// Layout error.
+    Layout(LayoutErr),
+    /// The iterator yielded fewer items than its reported length.
+    TooShort {
+        /// The reported length.
+        len: usize,
+        /// The number of items actually yielded.
+        found: usize,
+    },
+}
+
+impl fmt::Display for FromIterErr {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FromIterErr::Alloc(err) => write!(fmtr, "{}", err),
+            FromIterErr::Layout(err) => write!(fmtr, "{}", err),
+            FromIterErr::TooShort { len, found } => write!(
+                fmtr,
+                "the iterator yielded {} items but reported {}",
+                found, len
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromIterErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FromIterErr::Alloc(err) => Some(err),
+            FromIterErr::Layout(err) => Some(err),
+            FromIterErr::TooShort { .. } => None,
+        }
+    }
+}
+
+impl From<RawVecErr> for FromIterErr {
+    fn from(err: RawVecErr) -> Self {
+        match err {
+            RawVecErr::Alloc(err) => FromIterErr::Alloc(err),
+            RawVecErr::Layout(err) => FromIterErr::Layout(err),
+        }
+    }
+}
+
+/// Error returned when converting an allocation of bytes which are not valid
+/// UTF-8 into a string allocation. The bytes can be recovered.
+pub struct FromUtf8Err<A = Global>
+where
+    A: Allocator,
+{
+    bytes: OwnedAlloc<[u8], A>,
+    error: Utf8Error,
+}
+
+impl<A> FromUtf8Err<A>
+where
+    A: Allocator,
+{
+    pub(crate) fn new(bytes: OwnedAlloc<[u8], A>, error: Utf8Error) -> Self {
+        Self { bytes, error }
+    }
+
+    /// The bytes which failed the conversion.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the allocation of the bytes which failed the conversion.
+    pub fn into_bytes(self) -> OwnedAlloc<[u8], A> {
+        self.bytes
+    }
+
+    /// Details about the conversion failure.
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+impl<A> fmt::Debug for FromUtf8Err<A>
+where
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("FromUtf8Err")
+            .field("bytes", &self.as_bytes())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<A> fmt::Display for FromUtf8Err<A>
+where
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "{}", self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A> std::error::Error for FromUtf8Err<A>
+where
+    A: Allocator,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,249 @@
+use super::{AllocErr, Allocator, Global};
+use core::{
+    alloc::Layout,
+    ptr::NonNull,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+};
+
+/// The rule deciding which requests a `FailingAlloc` makes fail. Requests are
+/// both allocations and reallocations, counted from `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailPolicy {
+    /// No request fails.
+    Never,
+    /// Only the request with the given number fails.
+    Nth(usize),
+    /// Every request starting from the given number fails.
+    FromNth(usize),
+    /// Every request whose size is greater than the given number of bytes
+    /// fails.
+    AboveBytes(usize),
+    /// Each request fails with probability `1 / one_in`, using a pseudo-random
+    /// generator started from `seed`. The same seed always yields the same
+    /// sequence of failures.
+    Random {
+        /// Seed of the pseudo-random generator.
+        seed: u64,
+        /// Inverse of the probability of failing.
+        one_in: u64,
+    },
+}
+
+/// An allocator wrapper which makes requests fail according to a `FailPolicy`,
+/// useful to exercise the error paths of the `try_*` methods. Failed requests
+/// are never forwarded to the inner allocator, so a failed reallocation leaves
+/// the original block untouched. A reference to it is also an `Allocator`.
+///
+/// ```rust
+/// use tux_owned_alloc::{FailPolicy, FailingAlloc, RawVec, RawVecErr};
+///
+/// let failing = FailingAlloc::new(FailPolicy::Nth(2));
+/// let mut vec = RawVec::<u8, _>::try_with_capacity_in(4, &failing).unwrap();
+///
+/// match vec.try_resize(16) {
+///     Err(RawVecErr::Alloc(err)) => assert_eq!(err.layout.size(), 16),
+///     res => panic!("unexpected {:?}", res),
+/// }
+/// assert_eq!(vec.cap(), 4);
+/// ```
+#[derive(Debug)]
+pub struct FailingAlloc<A = Global>
+where
+    A: Allocator,
+{
+    inner: A,
+    policy: FailPolicy,
+    requests: AtomicUsize,
+    failures: AtomicUsize,
+    rng: AtomicU64,
+}
+
+impl FailingAlloc {
+    /// Creates a failing allocator wrapping the global allocator.
+    pub fn new(policy: FailPolicy) -> Self {
+        Self::wrap(Global, policy)
+    }
+}
+
+impl<A> FailingAlloc<A>
+where
+    A: Allocator,
+{
+    /// Creates a failing allocator wrapping the given allocator.
+    pub fn wrap(inner: A, policy: FailPolicy) -> Self {
+        let seed = match policy {
+            FailPolicy::Random { seed, .. } => seed,
+            _ => 0,
+        };
+
+        Self {
+            inner,
+            policy,
+            requests: AtomicUsize::new(0),
+            failures: AtomicUsize::new(0),
+            rng: AtomicU64::new(seed),
+        }
+    }
+
+    /// Returns a reference to the wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// The policy deciding which requests fail.
+    pub fn policy(&self) -> FailPolicy {
+        self.policy
+    }
+
+    /// Number of requests received so far, including the failed ones.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Relaxed)
+    }
+
+    /// Number of requests which failed so far.
+    pub fn failures(&self) -> usize {
+        self.failures.load(Relaxed)
+    }
+
+    fn check(&self, layout: Layout) -> Result<(), AllocErr> {
+        let nth = self.requests.fetch_add(1, Relaxed) + 1;
+
+        let fails = match self.policy {
+            FailPolicy::Never => false,
+            FailPolicy::Nth(n) => nth == n,
+            FailPolicy::FromNth(n) => nth >= n,
+            FailPolicy::AboveBytes(max) => layout.size() > max,
+            FailPolicy::Random { one_in, .. } => {
+                one_in != 0 && self.next_random().is_multiple_of(one_in)
+            },
+        };
+
+        if fails {
+            self.failures.fetch_add(1, Relaxed);
+            Err(AllocErr { layout })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn next_random(&self) -> u64 {
+        // splitmix64, good enough for testing and trivial to reproduce.
+        let state = self
+            .rng
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut mixed = state;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^ (mixed >> 31)
+    }
+}
+
+unsafe impl<A> Allocator for FailingAlloc<A>
+where
+    A: Allocator,
+{
+    fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        self.check(layout)?;
+        self.inner.alloc(layout)
+    }
+
+    fn alloc_zeroed(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        self.check(layout)?;
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        let new_layout = Layout::from_size_align(new_size, layout.align())
+            .map_err(|_| AllocErr { layout })?;
+        self.check(new_layout)?;
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        super::{Allocator, OwnedAlloc, RawVec, RawVecErr, UninitAlloc},
+        FailPolicy,
+        FailingAlloc,
+    };
+    use alloc::vec::Vec;
+    use core::alloc::Layout;
+
+    #[test]
+    fn nth_fails_once() {
+        let failing = FailingAlloc::new(FailPolicy::Nth(2));
+        assert!(UninitAlloc::<u32, _>::try_new_in(&failing).is_ok());
+        assert!(OwnedAlloc::try_new_in(5u32, &failing).is_err());
+        assert!(OwnedAlloc::try_new_in(5u32, &failing).is_ok());
+        assert_eq!((failing.requests(), failing.failures()), (3, 1));
+    }
+
+    #[test]
+    fn above_bytes() {
+        let failing = FailingAlloc::new(FailPolicy::AboveBytes(64));
+        assert!(RawVec::<u8, _>::try_with_capacity_in(64, &failing).is_ok());
+
+        let res = RawVec::<u8, _>::try_with_capacity_in(65, &failing);
+        match res {
+            Err(RawVecErr::Alloc(err)) => assert_eq!(err.layout.size(), 65),
+            res => panic!("unexpected {:?}", res),
+        }
+    }
+
+    #[test]
+    fn failed_resize_keeps_contents() {
+        let failing = FailingAlloc::new(FailPolicy::FromNth(2));
+        let mut vec = RawVec::<u32, _>::with_capacity_in(8, &failing);
+        let raw = vec.raw();
+        for (i, elem) in unsafe { vec.as_mut_slice() }.iter_mut().enumerate() {
+            *elem = i as u32;
+        }
+
+        assert!(vec.try_resize(1024).is_err());
+        assert!(vec.try_resize(2).is_err());
+        assert_eq!(vec.cap(), 8);
+        assert_eq!(vec.raw(), raw);
+        assert_eq!(unsafe { vec.as_slice() }, &[0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn realloc_overflow() {
+        let failing = FailingAlloc::new(FailPolicy::Never);
+        let layout = Layout::from_size_align(8, 64).unwrap();
+        let ptr = failing.alloc(layout).unwrap();
+        unsafe {
+            let res = failing.realloc(ptr, layout, isize::MAX as usize - 10);
+            assert_eq!(res.unwrap_err().layout, layout);
+            failing.dealloc(ptr, layout);
+        }
+        assert_eq!((failing.requests(), failing.failures()), (1, 0));
+    }
+
+    #[test]
+    fn random_is_reproducible() {
+        let run = || {
+            let failing = FailingAlloc::new(FailPolicy::Random {
+                seed: 42,
+                one_in: 3,
+            });
+            (0 .. 64)
+                .map(|i| OwnedAlloc::try_new_in(i, &failing).is_ok())
+                .collect::<Vec<_>>()
+        };
+
+        let first = run();
+        assert_eq!(first, run());
+        assert!(first.contains(&true) && first.contains(&false));
+    }
+}
@@ -0,0 +1,351 @@
+use super::{
+    Allocator,
+    FromIterErr,
+    Global,
+    LayoutErr,
+    OwnedAlloc,
+    RawVecErr,
+    UninitAlloc,
+};
+use alloc::alloc::handle_alloc_error;
+use core::{
+    alloc::Layout,
+    mem,
+    ptr::{self, NonNull},
+};
+
+/// A dynamically sized type made of a header and a trailing slice, stored in a
+/// single block of memory. Allocations of it, such as
+/// `OwnedAlloc<HeaderSlice<H, T>>`, need a single allocation instead of one for
+/// the header and one for the slice.
+///
+/// ```rust
+/// use tux_owned_alloc::{HeaderSlice, OwnedAlloc};
+///
+/// let packet = OwnedAlloc::<HeaderSlice<u16, u8>>::from_header_and_iter(
+///     0xCAFE,
+///     vec![1, 2, 3],
+/// );
+///
+/// assert_eq!(packet.header, 0xCAFE);
+/// assert_eq!(&packet.slice, &[1, 2, 3]);
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct HeaderSlice<H, T> {
+    /// The header stored before the slice.
+    pub header: H,
+    /// The trailing slice.
+    pub slice: [T],
+}
+
+impl<H, T> HeaderSlice<H, T> {
+    /// Computes the layout of a `HeaderSlice` whose slice has `len` elements.
+    /// In case of overflow calculating the total size, `Err` is returned.
+    pub fn layout(len: usize) -> Result<Layout, LayoutErr> {
+        let (layout, _) = Layout::new::<H>().extend(Layout::array::<T>(len)?)?;
+        Ok(layout.pad_to_align())
+    }
+
+    fn make_ptr(start: NonNull<u8>, len: usize) -> NonNull<Self> {
+        let start = start.as_ptr() as *mut T;
+        let slice = ptr::slice_from_raw_parts_mut(start, len);
+        unsafe { NonNull::new_unchecked(slice as *mut Self) }
+    }
+}
+
+impl<H, T> UninitAlloc<HeaderSlice<H, T>> {
+    /// Creates room for a header and a slice of `len` elements. In case of
+    /// allocation error, the handler registered via stdlib is called. In case
+    /// of overflow calculating the total size, the function panics.
+    pub fn new_header_slice(len: usize) -> Self {
+        Self::new_header_slice_in(len, Global)
+    }
+
+    /// Creates room for a header and a slice of `len` elements. In case of
+    /// allocation error or overflow calculating the total size, `Err` is
+    /// returned.
+    pub fn try_new_header_slice(len: usize) -> Result<Self, RawVecErr> {
+        Self::try_new_header_slice_in(len, Global)
+    }
+}
+
+impl<H, T, A> UninitAlloc<HeaderSlice<H, T>, A>
+where
+    A: Allocator,
+{
+    /// Creates room for a header and a slice of `len` elements using the given
+    /// allocator. In case of allocation error, the handler registered via
+    /// stdlib is called. In case of overflow calculating the total size, the
+    /// function panics.
+    pub fn new_header_slice_in(len: usize, alloc: A) -> Self {
+        match Self::try_new_header_slice_in(len, alloc) {
+            Ok(this) => this,
+            Err(RawVecErr::Alloc(err)) => handle_alloc_error(err.layout),
+            Err(RawVecErr::Layout(err)) => {
+                panic!("Length overflows memory size: {}", err)
+            },
+        }
+    }
+
+    /// Creates room for a header and a slice of `len` elements using the given
+    /// allocator. In case of allocation error or overflow calculating the
+    /// total size, `Err` is returned.
+    pub fn try_new_header_slice_in(
+        len: usize,
+        alloc: A,
+    ) -> Result<Self, RawVecErr> {
+        let layout = HeaderSlice::<H, T>::layout(len)?;
+
+        let start = if layout.size() == 0 {
+            let dangling = ptr::without_provenance_mut::<u8>(layout.align());
+            unsafe { NonNull::new_unchecked(dangling) }
+        } else {
+            alloc.alloc(layout)?
+        };
+
+        let nnptr = HeaderSlice::make_ptr(start, len);
+        Ok(unsafe { Self::from_raw_in(nnptr, alloc) })
+    }
+
+    /// Initializes the header to the passed value and the slice with the items
+    /// of the passed iterator, in order. If the iterator yields fewer items
+    /// than the slice length, the function panics. Extra items are not
+    /// consumed. On panic, the values already initialized are dropped and the
+    /// allocation is freed.
+    pub fn init_header_slice<I>(
+        self,
+        header: H,
+        iter: I,
+    ) -> OwnedAlloc<HeaderSlice<H, T>, A>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        match self.try_init_header_slice(header, iter) {
+            Ok(alloc) => alloc,
+            Err(_) => panic!("iterator shorter than the header slice length"),
+        }
+    }
+
+    /// Initializes the header to the passed value and the slice with the items
+    /// of the passed iterator, in order. If the iterator yields fewer items
+    /// than the slice length, the values already initialized are dropped, the
+    /// allocation is freed and `Err` is returned. Extra items are not
+    /// consumed. On panic, the values already initialized are dropped and the
+    /// allocation is freed as well.
+    pub fn try_init_header_slice<I>(
+        self,
+        header: H,
+        iter: I,
+    ) -> Result<OwnedAlloc<HeaderSlice<H, T>, A>, FromIterErr>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let raw = self.raw().as_ptr();
+        let len = (raw as *mut [T]).len();
+        let mut iter = iter.into_iter();
+
+        unsafe {
+            ptr::addr_of_mut!((*raw).header).write(header);
+        }
+
+        let mut guard = Guard { raw, init: 0 };
+        let start = unsafe { ptr::addr_of_mut!((*raw).slice) as *mut T };
+
+        while guard.init < len {
+            let item = iter
+                .next()
+                .ok_or(FromIterErr::TooShort { len, found: guard.init })?;
+            unsafe { start.add(guard.init).write(item) };
+            guard.init += 1;
+        }
+
+        mem::forget(guard);
+        let (nnptr, alloc) = self.into_raw_with_alloc();
+        Ok(unsafe { OwnedAlloc::from_raw_in(nnptr, alloc) })
+    }
+}
+
+/// Drops the header and the initialized prefix of a header slice being
+/// initialized, on early return or unwind.
+struct Guard<H, T> {
+    raw: *mut HeaderSlice<H, T>,
+    init: usize,
+}
+
+impl<H, T> Drop for Guard<H, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::addr_of_mut!((*self.raw).header).drop_in_place();
+            let start = ptr::addr_of_mut!((*self.raw).slice) as *mut T;
+            ptr::slice_from_raw_parts_mut(start, self.init).drop_in_place();
+        }
+    }
+}
+
+impl<H, T> OwnedAlloc<HeaderSlice<H, T>> {
+    /// Creates a header slice allocation initialized to the passed header and
+    /// the items of the passed iterator. In case of allocation error, the
+    /// handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, or if the iterator yields fewer items than
+    /// its reported length, the function panics.
+    pub fn from_header_and_iter<I>(header: H, iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        Self::from_header_and_iter_in(header, iter, Global)
+    }
+
+    /// Creates a header slice allocation initialized to the passed header and
+    /// the items of the passed iterator. In case of allocation error or
+    /// overflow calculating the total size, `Err` is returned. If the iterator
+    /// yields fewer items than its reported length, the values already yielded
+    /// are dropped, the allocation is freed and `Err` is returned.
+    pub fn try_from_header_and_iter<I>(
+        header: H,
+        iter: I,
+    ) -> Result<Self, FromIterErr>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        Self::try_from_header_and_iter_in(header, iter, Global)
+    }
+}
+
+impl<H, T, A> OwnedAlloc<HeaderSlice<H, T>, A>
+where
+    A: Allocator,
+{
+    /// Creates a header slice allocation using the given allocator, initialized
+    /// to the passed header and the items of the passed iterator. In case of
+    /// allocation error, the handler registered via stdlib is called. In case
+    /// of overflow calculating the total size, or if the iterator yields fewer
+    /// items than its reported length, the function panics.
+    pub fn from_header_and_iter_in<I>(header: H, iter: I, alloc: A) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        UninitAlloc::new_header_slice_in(iter.len(), alloc)
+            .init_header_slice(header, iter)
+    }
+
+    /// Creates a header slice allocation using the given allocator, initialized
+    /// to the passed header and the items of the passed iterator. In case of
+    /// allocation error or overflow calculating the total size, `Err` is
+    /// returned. If the iterator yields fewer items than its reported length,
+    /// the values already yielded are dropped, the allocation is freed and
+    /// `Err` is returned.
+    pub fn try_from_header_and_iter_in<I>(
+        header: H,
+        iter: I,
+        alloc: A,
+    ) -> Result<Self, FromIterErr>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        UninitAlloc::try_new_header_slice_in(iter.len(), alloc)?
+            .try_init_header_slice(header, iter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::{
+        super::{CountingAlloc, OwnedAlloc},
+        FromIterErr,
+        HeaderSlice,
+        UninitAlloc,
+    };
+    use alloc::{rc::Rc, string::String, vec, vec::Vec};
+    use core::{alloc::Layout, mem};
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn layout_matches_value() {
+        let alloc = OwnedAlloc::from_header_and_iter(1u8, vec![2u64, 3, 4]);
+        assert_eq!(
+            HeaderSlice::<u8, u64>::layout(3).unwrap(),
+            Layout::for_value(&*alloc)
+        );
+        assert_eq!(mem::size_of_val(&*alloc), 32);
+        assert_eq!((alloc.header, &alloc.slice), (1, &[2, 3, 4][..]));
+    }
+
+    #[test]
+    fn zero_sized() {
+        let alloc = OwnedAlloc::from_header_and_iter((), Vec::<()>::new());
+        assert_eq!(alloc.slice.len(), 0);
+
+        let alloc = OwnedAlloc::from_header_and_iter((), vec![(); 5]);
+        assert_eq!(alloc.slice.len(), 5);
+    }
+
+    #[test]
+    fn drops_everything() {
+        let counter = Rc::new(());
+        let items = (0 .. 4).map(|_| counter.clone()).collect::<Vec<_>>();
+        let alloc = OwnedAlloc::from_header_and_iter(counter.clone(), items);
+        assert_eq!(Rc::strong_count(&counter), 6);
+
+        drop(alloc);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn short_iterator_panics_without_leaking() {
+        let counter = Rc::new(());
+        let items = vec![counter.clone()];
+        let uninit =
+            UninitAlloc::<HeaderSlice<String, Rc<()>>>::new_header_slice(3);
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            uninit.init_header_slice(String::from("header"), items)
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn try_short_iterator_errs_without_leaking() {
+        struct ShortIter(Rc<()>, usize);
+
+        impl Iterator for ShortIter {
+            type Item = Rc<()>;
+
+            fn next(&mut self) -> Option<Rc<()>> {
+                self.1 = self.1.checked_sub(1)?;
+                Some(self.0.clone())
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (3, Some(3))
+            }
+        }
+
+        impl ExactSizeIterator for ShortIter {}
+
+        let counter = Rc::new(());
+        let alloc = CountingAlloc::new();
+        let res = OwnedAlloc::try_from_header_and_iter_in(
+            counter.clone(),
+            ShortIter(counter.clone(), 2),
+            &alloc,
+        );
+
+        match res {
+            Err(FromIterErr::TooShort { len: 3, found: 2 }) => (),
+            res => panic!("unexpected {:?}", res),
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+        assert_eq!(alloc.stats().live_bytes, 0);
+    }
+}
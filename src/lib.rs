@@ -1,3 +1,8 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(
+    feature = "nightly",
+    feature(coerce_unsized, unsize, fn_traits, unboxed_closures, tuple_trait)
+)]
 #![warn(missing_docs)]
 //! Owned Allocations. A crate to help reducing manual memory management errors.
 //!
@@ -7,25 +12,83 @@
 //! the `OwnedAlloc` and getting an `UninitAlloc` back.
 //!
 //! For vec-like structures, a type `RawVec` is available, pretty similar to the
-//! one used by the standard library. Currently, no other help is provided for
-//! arrays/vectors.
+//! one used by the standard library. On top of it, `OwnedVec` tracks the length
+//! and safely manages the elements, just like a `Vec`, and `OwnedDeque` is a
+//! ring buffer with fallible growth. A `RawSoa` keeps several parallel arrays
+//! of the same capacity in a single allocation, and a `HeaderSlice` stores a
+//! header and a trailing slice in a single allocation. A `PartialSlice` tracks
+//! which of its elements are initialized with a bitmap, for sparse tables.
 //!
 //! There is also a type `Cache`, which is actually more general than
 //! allocation, but may be useful for allocations. It can save unused
 //! allocations requested on a tight loop.
+//!
+//! For many small allocations which die together, an `Arena` is available. It
+//! hands out `UninitAlloc`s and `OwnedAlloc`s borrowing the arena and frees all
+//! of their memory at once. For many same-typed allocations which churn, a
+//! `Pool` keeps freed slots in a free list and reuses them.
+//!
+//! Every allocation type is generic over an `Allocator`, a stable-Rust
+//! allocator trait defined by this crate. By default, the `Global` allocator,
+//! i.e. the one registered via stdlib, is used. A `CountingAlloc` wrapper
+//! records statistics about the requests of any allocator, a `FailingAlloc`
+//! wrapper makes chosen requests fail to exercise error paths, and an `Aligned`
+//! wrapper raises the alignment of allocations, e.g. for SIMD or pages.
+//!
+//! The crate is `no_std` compatible, requiring only the `alloc` crate. The
+//! default `std` feature only adds implementations of `std::error::Error` for
+//! the error types. Disable default features to build without `std`.
+//! `CountingAlloc` and `FailingAlloc` require atomic operations and are only
+//! available on targets supporting them. The `nightly` feature enables
+//! unsizing coercions, e.g. from `OwnedAlloc<T>` to `OwnedAlloc<dyn Trait>`,
+//! which are otherwise done with the `unsize!` macro.
 
+extern crate alloc;
+
+#[macro_use]
+mod macros;
+mod aligned;
+mod allocator;
+mod arena;
+mod pool;
 mod uninit;
 mod owned;
+mod owned_vec;
+mod owned_deque;
+mod partial_slice;
 mod cache;
+#[cfg(target_has_atomic = "ptr")]
+mod counting;
 mod raw_vec;
+mod raw_soa;
 mod maybe_uninit;
 mod err;
+mod zeroable;
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+mod failing;
+mod header_slice;
 
 pub use self::{
+    aligned::Aligned,
+    allocator::{Allocator, Global},
+    arena::Arena,
     cache::Cache,
-    err::{AllocErr, LayoutErr, RawVecErr},
+    err::{AllocErr, FromIterErr, FromUtf8Err, LayoutErr, RawVecErr},
+    header_slice::HeaderSlice,
     maybe_uninit::MaybeUninitAlloc,
     owned::OwnedAlloc,
-    raw_vec::RawVec,
+    owned_deque::OwnedDeque,
+    owned_vec::{Drain, OwnedVec},
+    partial_slice::PartialSlice,
+    pool::{Pool, PoolBox},
+    raw_soa::{RawSoa, SoaColumns},
+    raw_vec::{GrowthPolicy, RawVec},
     uninit::UninitAlloc,
+    zeroable::Zeroable,
 };
+
+#[cfg(target_has_atomic = "ptr")]
+pub use self::counting::{AllocStats, CountingAlloc};
+
+#[cfg(all(target_has_atomic = "ptr", target_has_atomic = "64"))]
+pub use self::failing::{FailPolicy, FailingAlloc};
@@ -0,0 +1,23 @@
+/// Converts an `OwnedAlloc<T, A>` into an `OwnedAlloc<U, A>` where `U` is an
+/// unsized type `T` can be coerced to, e.g. a trait object `dyn Trait` or a
+/// slice `[E]` from an array `[E; N]`. No allocation is performed. This is the
+/// stable counterpart of the coercion enabled by the `nightly` feature.
+///
+/// ```rust
+/// use std::fmt::Debug;
+/// use tux_owned_alloc::{unsize, OwnedAlloc};
+///
+/// let alloc: OwnedAlloc<dyn Debug> = unsize!(OwnedAlloc::new(5), dyn Debug);
+/// assert_eq!(format!("{:?}", &*alloc), "5");
+///
+/// let slice = unsize!(OwnedAlloc::new([1, 2, 3]), [i32]);
+/// assert_eq!(&*slice, &[1, 2, 3]);
+/// ```
+#[macro_export]
+macro_rules! unsize {
+    ($alloc:expr, $target:ty) => {{
+        let (nnptr, alloc) = $crate::OwnedAlloc::into_raw_with_alloc($alloc);
+        let nnptr: ::core::ptr::NonNull<$target> = nnptr;
+        unsafe { $crate::OwnedAlloc::from_raw_in(nnptr, alloc) }
+    }};
+}
@@ -1,25 +1,30 @@
-use super::{OwnedAlloc, UninitAlloc};
-use std::fmt;
+use super::{Allocator, Global, OwnedAlloc, UninitAlloc};
+use core::{fmt, mem::MaybeUninit, ptr};
 
 /// Pointer to memory allocaation that might be either initialized or
 /// uninitialized. For the drop checker, the type acts as if it contains a `T`
-/// due to usage of `PhantomData<T>`.
-pub enum MaybeUninitAlloc<T>
+/// due to usage of `PhantomData<T>`. The memory is obtained from the allocator
+/// `A`, which is the global allocator by default.
+pub enum MaybeUninitAlloc<T, A = Global>
 where
     T: ?Sized,
+    A: Allocator,
 {
     /// Initialized allocation.
-    Init(OwnedAlloc<T>),
+    Init(OwnedAlloc<T, A>),
 
     /// Uninitialized allocation.
-    Uninit(UninitAlloc<T>),
+    Uninit(UninitAlloc<T, A>),
 }
 
-impl<T> MaybeUninitAlloc<T> {
+impl<T, A> MaybeUninitAlloc<T, A>
+where
+    A: Allocator,
+{
     /// If the allocation was initialized, this is a no-op. If it wasn't, the
     /// passed function is called and its return value is used to initialize the
     /// memory. In both cases, an allocation considered initialized is returned.
-    pub fn or_init<F>(self, init: F) -> OwnedAlloc<T>
+    pub fn or_init<F>(self, init: F) -> OwnedAlloc<T, A>
     where
         F: FnOnce() -> T,
     {
@@ -28,21 +33,122 @@ impl<T> MaybeUninitAlloc<T> {
             MaybeUninitAlloc::Uninit(ptr) => ptr.init(init()),
         }
     }
+
+    /// If the allocation was initialized, this is a no-op. If it wasn't, the
+    /// passed function is called with the uninitialized memory as a
+    /// `MaybeUninit<T>`, and it must initialize the memory and return the
+    /// reference to the initialized value (see `UninitAlloc::init_uninit`). In
+    /// both cases, an allocation considered initialized is returned.
+    ///
+    /// # Panics
+    /// Panics if the returned reference does not point to the allocation.
+    pub fn or_init_uninit<F>(self, init: F) -> OwnedAlloc<T, A>
+    where
+        F: FnOnce(&mut MaybeUninit<T>) -> &mut T,
+    {
+        match self {
+            MaybeUninitAlloc::Init(ptr) => ptr,
+            MaybeUninitAlloc::Uninit(ptr) => ptr.init_uninit(init),
+        }
+    }
+
+    /// Moves the value out, if the memory is initialized, and leaves the
+    /// allocation uninitialized in place. The memory is kept for later use.
+    pub fn take(&mut self) -> Option<T> {
+        let val = match self {
+            MaybeUninitAlloc::Init(ptr) => unsafe { ptr.raw().as_ptr().read() },
+            MaybeUninitAlloc::Uninit(_) => return None,
+        };
+
+        unsafe {
+            self.transition(|this| match this {
+                MaybeUninitAlloc::Init(ptr) => ptr.forget_inner().into(),
+                uninit => uninit,
+            })
+        };
+        Some(val)
+    }
+
+    /// Initializes the memory to the passed value, moving the previous value
+    /// out and returning it, if the memory was initialized. The allocation is
+    /// reused.
+    pub fn replace(&mut self, val: T) -> Option<T> {
+        let prev = self.take();
+        self.insert(val);
+        prev
+    }
+
+    /// Initializes the memory to the passed value, dropping the previous value
+    /// if the memory was initialized, and returns a mutable reference to the
+    /// new value. The allocation is reused.
+    pub fn insert(&mut self, val: T) -> &mut T {
+        if let MaybeUninitAlloc::Init(ptr) = self {
+            **ptr = val;
+        } else {
+            unsafe {
+                self.transition(|this| match this {
+                    MaybeUninitAlloc::Uninit(ptr) => ptr.init(val).into(),
+                    init => init,
+                })
+            };
+        }
+
+        match self {
+            MaybeUninitAlloc::Init(ptr) => ptr,
+            MaybeUninitAlloc::Uninit(_) => unreachable!(),
+        }
+    }
+
+    /// Returns a mutable reference to the value. If the memory is
+    /// uninitialized, it is first initialized to the return value of the
+    /// passed function.
+    pub fn get_or_insert_with<F>(&mut self, init: F) -> &mut T
+    where
+        F: FnOnce() -> T,
+    {
+        if let MaybeUninitAlloc::Uninit(_) = self {
+            self.insert(init());
+        }
+
+        match self {
+            MaybeUninitAlloc::Init(ptr) => ptr,
+            MaybeUninitAlloc::Uninit(_) => unreachable!(),
+        }
+    }
+
+    /// If the memory is initialized, its value is replaced by the return value
+    /// of the passed function called with the previous value. The allocation
+    /// is reused. If the function panics, the allocation is freed.
+    pub fn map<F>(self, map: F) -> Self
+    where
+        F: FnOnce(T) -> T,
+    {
+        match self {
+            MaybeUninitAlloc::Init(ptr) => {
+                let (val, uninit) = ptr.move_inner();
+                uninit.init(map(val)).into()
+            },
+            uninit => uninit,
+        }
+    }
 }
 
-impl<T> MaybeUninitAlloc<T>
+impl<T, A> MaybeUninitAlloc<T, A>
 where
     T: ?Sized,
+    A: Allocator,
 {
     /// If the allocation was initialized, this is a no-op. If it wasn't, the
     /// passed function is called with a mutable reference to the uninitialized
     /// memory and the function is expected to initialize the memory. In both
-    /// cases, an allocation considered initialized is returned.
+    /// cases, an allocation considered initialized is returned. If the function
+    /// panics, the allocation is freed, but whatever it has written is not
+    /// dropped.
     ///
     /// # Safety
     /// This function is `unsafe` because the passed function might not
     /// initialize the memory correctly.
-    pub unsafe fn or_init_in_place<F>(self, init: F) -> OwnedAlloc<T>
+    pub unsafe fn or_init_in_place<F>(self, init: F) -> OwnedAlloc<T, A>
     where
         F: FnOnce(&mut T),
     {
@@ -63,14 +169,32 @@ where
     /// Tests if the allocation is uninitialized.
     pub fn is_uninitialized(&self) -> bool {
         match self {
-            MaybeUninitAlloc::Init(_) => true,
-            MaybeUninitAlloc::Uninit(_) => false,
+            MaybeUninitAlloc::Init(_) => false,
+            MaybeUninitAlloc::Uninit(_) => true,
+        }
+    }
+
+    /// Returns a reference to the value if the memory is initialized, or
+    /// `None` otherwise.
+    pub fn as_ref(&self) -> Option<&T> {
+        match self {
+            MaybeUninitAlloc::Init(ptr) => Some(&**ptr),
+            MaybeUninitAlloc::Uninit(_) => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value if the memory is initialized,
+    /// or `None` otherwise.
+    pub fn as_mut(&mut self) -> Option<&mut T> {
+        match self {
+            MaybeUninitAlloc::Init(ptr) => Some(&mut **ptr),
+            MaybeUninitAlloc::Uninit(_) => None,
         }
     }
 
     /// If the memory is initialized, this function drops its content. In any
     /// case, the allocation now with uninitialized content is returned.
-    pub fn drop_in_place(self) -> UninitAlloc<T> {
+    pub fn drop_in_place(self) -> UninitAlloc<T, A> {
         match self {
             MaybeUninitAlloc::Init(ptr) => ptr.drop_in_place(),
             MaybeUninitAlloc::Uninit(ptr) => ptr,
@@ -78,7 +202,7 @@ where
     }
 
     /// Encodes this type as a `Result` with an `OwnedAlloc` as `Ok`.
-    pub fn init_as_ok(self) -> Result<OwnedAlloc<T>, UninitAlloc<T>> {
+    pub fn init_as_ok(self) -> Result<OwnedAlloc<T, A>, UninitAlloc<T, A>> {
         match self {
             MaybeUninitAlloc::Init(ptr) => Ok(ptr),
             MaybeUninitAlloc::Uninit(ptr) => Err(ptr),
@@ -86,7 +210,7 @@ where
     }
 
     /// Encodes this type as a `Result` with an `UninitAlloc` as `Ok`.
-    pub fn uninit_as_ok(self) -> Result<UninitAlloc<T>, OwnedAlloc<T>> {
+    pub fn uninit_as_ok(self) -> Result<UninitAlloc<T, A>, OwnedAlloc<T, A>> {
         match self {
             MaybeUninitAlloc::Init(ptr) => Err(ptr),
             MaybeUninitAlloc::Uninit(ptr) => Ok(ptr),
@@ -96,15 +220,27 @@ where
     /// If the memory is uninitialized, `None` is returned. If it is
     /// initialized, the passed function is called with a mutable reference to
     /// the allocation, and its return value is wrapped into a `Some`.
-    pub fn modify<F, A>(&mut self, visit: F) -> Option<A>
+    pub fn modify<F, R>(&mut self, visit: F) -> Option<R>
     where
-        F: FnOnce(&mut T) -> A,
+        F: FnOnce(&mut T) -> R,
     {
         match self {
             MaybeUninitAlloc::Init(ptr) => Some(visit(&mut **ptr)),
             MaybeUninitAlloc::Uninit(_) => None,
         }
     }
+
+    /// Replaces `self` by the return value of the passed function called with
+    /// `self` moved out.
+    ///
+    /// # Safety
+    /// The passed function must not panic, or `self` would be dropped twice.
+    unsafe fn transition<F>(&mut self, trans: F)
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        ptr::write(self, trans(ptr::read(self)));
+    }
 }
 
 impl<T> From<T> for MaybeUninitAlloc<T> {
@@ -113,31 +249,36 @@ impl<T> From<T> for MaybeUninitAlloc<T> {
     }
 }
 
-impl<T> From<OwnedAlloc<T>> for MaybeUninitAlloc<T>
+impl<T, A> From<OwnedAlloc<T, A>> for MaybeUninitAlloc<T, A>
 where
     T: ?Sized,
+    A: Allocator,
 {
-    fn from(alloc: OwnedAlloc<T>) -> Self {
+    fn from(alloc: OwnedAlloc<T, A>) -> Self {
         MaybeUninitAlloc::Init(alloc)
     }
 }
 
-impl<T> From<UninitAlloc<T>> for MaybeUninitAlloc<T>
+impl<T, A> From<UninitAlloc<T, A>> for MaybeUninitAlloc<T, A>
 where
     T: ?Sized,
+    A: Allocator,
 {
-    fn from(alloc: UninitAlloc<T>) -> Self {
+    fn from(alloc: UninitAlloc<T, A>) -> Self {
         MaybeUninitAlloc::Uninit(alloc)
     }
 }
 
-impl<T> fmt::Debug for MaybeUninitAlloc<T>
+impl<T, A> fmt::Debug for MaybeUninitAlloc<T, A>
 where
     T: ?Sized,
+    A: Allocator,
 {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            MaybeUninitAlloc::Init(ptr) => write!(fmtr, "Init({:?})", ptr),
+            MaybeUninitAlloc::Init(ptr) => {
+                write!(fmtr, "Init({:?})", ptr.raw())
+            },
             MaybeUninitAlloc::Uninit(ptr) => write!(fmtr, "Uninit({:?})", ptr),
         }
     }
@@ -146,6 +287,62 @@ where
 #[cfg(test)]
 mod test {
     use super::{super::UninitAlloc, MaybeUninitAlloc};
+    use alloc::{string::String, vec::Vec};
+
+    fn raw<T>(alloc: &MaybeUninitAlloc<T>) -> *mut T {
+        match alloc {
+            MaybeUninitAlloc::Init(ptr) => ptr.raw().as_ptr(),
+            MaybeUninitAlloc::Uninit(ptr) => ptr.raw().as_ptr(),
+        }
+    }
+
+    #[test]
+    fn init_state() {
+        let init = MaybeUninitAlloc::from(5);
+        assert!(init.is_initialized() && !init.is_uninitialized());
+
+        let uninit = MaybeUninitAlloc::<u8>::from(UninitAlloc::new());
+        assert!(!uninit.is_initialized() && uninit.is_uninitialized());
+    }
+
+    #[test]
+    fn take_replace_insert_reuse_memory() {
+        let mut alloc = MaybeUninitAlloc::from(String::from("first"));
+        let ptr = raw(&alloc);
+
+        assert_eq!(alloc.take().as_deref(), Some("first"));
+        assert!(alloc.is_uninitialized());
+        assert_eq!(alloc.take(), None);
+        assert_eq!(alloc.as_ref(), None);
+
+        assert_eq!(alloc.replace(String::from("second")), None);
+        let prev = alloc.replace(String::from("third"));
+        assert_eq!(prev.as_deref(), Some("second"));
+
+        alloc.insert(String::from("fourth")).push('!');
+        assert_eq!(alloc.as_ref().map(String::as_str), Some("fourth!"));
+        assert_eq!(raw(&alloc), ptr);
+    }
+
+    #[test]
+    fn get_or_insert_with_and_map() {
+        let mut alloc = MaybeUninitAlloc::<Vec<u8>>::from(UninitAlloc::new());
+        let ptr = raw(&alloc);
+
+        alloc.get_or_insert_with(Vec::new).push(1);
+        alloc.get_or_insert_with(|| unreachable!()).push(2);
+        alloc.as_mut().unwrap().push(3);
+
+        let alloc = alloc.map(|mut vec| {
+            vec.reverse();
+            vec
+        });
+        assert_eq!(alloc.as_ref().map(Vec::as_slice), Some(&[3, 2, 1][..]));
+        assert_eq!(raw(&alloc), ptr);
+
+        let uninit = MaybeUninitAlloc::<u8>::from(UninitAlloc::new());
+        assert!(uninit.map(|_| unreachable!()).is_uninitialized());
+    }
 
     #[test]
     fn or_init_is_noop_if_initialized() {
@@ -161,6 +358,13 @@ mod test {
         assert_eq!(*init.or_init(|| 50), 50);
     }
 
+    #[test]
+    fn or_init_uninit_calls_if_uninit() {
+        let init = MaybeUninitAlloc::from(UninitAlloc::new());
+
+        assert_eq!(*init.or_init_uninit(|slot| slot.write(50)), 50);
+    }
+
     #[test]
     fn modifies() {
         let mut init = MaybeUninitAlloc::from(20);
@@ -1,23 +1,52 @@
-use super::{AllocErr, UninitAlloc};
-use std::{
-    alloc::{dealloc, Layout},
+use super::{
+    AllocErr,
+    Allocator,
+    FromIterErr,
+    FromUtf8Err,
+    Global,
+    OwnedVec,
+    UninitAlloc,
+    Zeroable,
+};
+use alloc::{boxed::Box, string::String};
+#[cfg(feature = "nightly")]
+use core::{
+    marker::{Tuple, Unsize},
+    ops::CoerceUnsized,
+};
+use core::{
+    alloc::Layout,
+    any::Any,
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    convert::Infallible,
     fmt,
+    future::Future,
+    hash::{Hash, Hasher},
+    iter::{self, FusedIterator},
     marker::PhantomData,
-    mem,
+    mem::{self, ManuallyDrop},
     ops::{Deref, DerefMut},
-    ptr::NonNull,
+    pin::Pin,
+    ptr::{self, NonNull},
+    str,
+    task::{Context, Poll},
 };
 
 /// Dynamic allocation of a `T` whose memory is considered fully initialized.
 /// The allocation and its content are freed on `drop`. Similar to a `Box`. If
 /// the size of the allocation is zero, no allocation is performed and a
 /// dangling pointer is used (just like in `std`). For the drop checker, the
-/// type acts as if it contains a `T` due to usage of `PhantomData<T>`.
-pub struct OwnedAlloc<T>
+/// type acts as if it contains a `T` due to usage of `PhantomData<T>`. The
+/// memory is obtained from the allocator `A`, which is the global allocator by
+/// default.
+pub struct OwnedAlloc<T, A = Global>
 where
     T: ?Sized,
+    A: Allocator,
 {
     nnptr: NonNull<T>,
+    alloc: A,
     _marker: PhantomData<T>,
 }
 
@@ -34,16 +63,372 @@ impl<T> OwnedAlloc<T> {
         UninitAlloc::try_new().map(|alloc| alloc.init(val))
     }
 
+    /// Creates a pinned allocation initialized to the passed argument. If `T`
+    /// does not implement `Unpin`, the value will never be moved out of it. In
+    /// case of allocation error, the handler registered via stdlib is called.
+    pub fn pin(val: T) -> Pin<Self> {
+        Self::new(val).into_pin()
+    }
+
+    /// Creates a pinned allocation initialized to the passed argument. If `T`
+    /// does not implement `Unpin`, the value will never be moved out of it. In
+    /// case of allocation error, `Err` is returned.
+    pub fn try_pin(val: T) -> Result<Pin<Self>, AllocErr> {
+        Self::try_new(val).map(Self::into_pin)
+    }
+}
+
+impl<T> OwnedAlloc<T>
+where
+    T: Zeroable,
+{
+    /// Creates an allocation initialized to the value whose bytes are all zero.
+    /// In case of allocation error, the handler registered via stdlib is
+    /// called.
+    pub fn new_zeroed() -> Self {
+        Self::new_zeroed_in(Global)
+    }
+
+    /// Creates an allocation initialized to the value whose bytes are all zero.
+    /// In case of allocation error, `Err` is returned.
+    pub fn try_new_zeroed() -> Result<Self, AllocErr> {
+        Self::try_new_zeroed_in(Global)
+    }
+}
+
+impl<T, A> OwnedAlloc<T, A>
+where
+    T: Zeroable,
+    A: Allocator,
+{
+    /// Creates an allocation using the given allocator, initialized to the
+    /// value whose bytes are all zero. In case of allocation error, the handler
+    /// registered via stdlib is called.
+    pub fn new_zeroed_in(alloc: A) -> Self {
+        unsafe { Self::assume_zeroed(UninitAlloc::new_zeroed_in(alloc)) }
+    }
+
+    /// Creates an allocation using the given allocator, initialized to the
+    /// value whose bytes are all zero. In case of allocation error, `Err` is
+    /// returned.
+    pub fn try_new_zeroed_in(alloc: A) -> Result<Self, AllocErr> {
+        UninitAlloc::try_new_zeroed_in(alloc)
+            .map(|alloc| unsafe { Self::assume_zeroed(alloc) })
+    }
+
+    unsafe fn assume_zeroed(alloc: UninitAlloc<T, A>) -> Self {
+        let (nnptr, alloc) = alloc.into_raw_with_alloc();
+        Self::from_raw_in(nnptr, alloc)
+    }
+}
+
+impl<T, A> OwnedAlloc<T, A>
+where
+    A: Allocator,
+{
+    /// Creates an allocation using the given allocator and initializes it to
+    /// the passed argument. In case of allocation error, the handler registered
+    /// via stdlib is called.
+    pub fn new_in(val: T, alloc: A) -> Self {
+        UninitAlloc::new_in(alloc).init(val)
+    }
+
+    /// Creates an allocation using the given allocator and initializes it to
+    /// the passed argument. In case of allocation error, `Err` is returned.
+    pub fn try_new_in(val: T, alloc: A) -> Result<Self, AllocErr> {
+        UninitAlloc::try_new_in(alloc).map(|alloc| alloc.init(val))
+    }
+
+    /// Creates a pinned allocation using the given allocator, initialized to
+    /// the passed argument. The allocator must live for `'static`, as in
+    /// `into_pin`. In case of allocation error, the handler registered via
+    /// stdlib is called.
+    pub fn pin_in(val: T, alloc: A) -> Pin<Self>
+    where
+        A: 'static,
+    {
+        Self::new_in(val, alloc).into_pin()
+    }
+
+    /// Creates a pinned allocation using the given allocator, initialized to
+    /// the passed argument. The allocator must live for `'static`, as in
+    /// `into_pin`. In case of allocation error, `Err` is returned.
+    pub fn try_pin_in(val: T, alloc: A) -> Result<Pin<Self>, AllocErr>
+    where
+        A: 'static,
+    {
+        Self::try_new_in(val, alloc).map(Self::into_pin)
+    }
+
     /// Moves the stored value out from the allocation. The value and the
     /// allocation now considered uninitialized are returned.
-    pub fn move_inner(self) -> (T, UninitAlloc<T>) {
-        let val = unsafe { self.nnptr.as_ptr().read() };
-        let alloc = unsafe { UninitAlloc::from_raw(self.nnptr) };
-        mem::forget(self);
+    pub fn move_inner(self) -> (T, UninitAlloc<T, A>) {
+        let (nnptr, alloc) = self.into_raw_with_alloc();
+        let val = unsafe { nnptr.as_ptr().read() };
+        let alloc = unsafe { UninitAlloc::from_raw_in(nnptr, alloc) };
         (val, alloc)
     }
+
+    /// Replaces the stored value with the passed one, keeping the allocation,
+    /// and returns the old value.
+    pub fn replace(&mut self, val: T) -> T {
+        mem::replace(&mut **self, val)
+    }
+
+    /// Takes the stored value, keeping the allocation and leaving the default
+    /// value of `T` in its place.
+    pub fn take(&mut self) -> T
+    where
+        T: Default,
+    {
+        mem::take(&mut **self)
+    }
+
+    /// Moves the stored value into the passed allocation, which becomes the
+    /// allocation of `self`. The previous allocation is returned, now
+    /// considered uninitialized, so it can be reused.
+    pub fn set_from(&mut self, alloc: UninitAlloc<T, A>) -> UninitAlloc<T, A> {
+        let val = unsafe { self.nnptr.as_ptr().read() };
+        mem::replace(self, alloc.init(val)).forget_inner()
+    }
+}
+
+impl<T> OwnedAlloc<[T]>
+where
+    T: Clone,
+{
+    /// Creates a slice allocation initialized to clones of the elements of the
+    /// passed slice. In case of allocation error, the handler registered via
+    /// stdlib is called. In case of overflow calculating the total size, the
+    /// function panics.
+    pub fn from_slice(slice: &[T]) -> Self {
+        Self::from_slice_in(slice, Global)
+    }
+
+    /// Creates a slice allocation of `len` elements initialized to clones of
+    /// the passed element. In case of allocation error, the handler registered
+    /// via stdlib is called. In case of overflow calculating the total size,
+    /// the function panics.
+    pub fn from_elem(elem: T, len: usize) -> Self {
+        Self::from_elem_in(elem, len, Global)
+    }
+}
+
+impl<T> OwnedAlloc<[T]> {
+    /// Creates a slice allocation initialized to the items of the passed
+    /// iterator, whose reported length is used as the slice length. In case of
+    /// allocation error or overflow calculating the total size, `Err` is
+    /// returned. If the iterator yields fewer items than its reported length,
+    /// the items already yielded are dropped, the allocation is freed and
+    /// `Err` is returned. Extra items are not consumed.
+    pub fn try_from_exact_iter<I>(iter: I) -> Result<Self, FromIterErr>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        Self::try_from_exact_iter_in(iter, Global)
+    }
+}
+
+impl<T, A> OwnedAlloc<[T], A>
+where
+    A: Allocator,
+{
+    /// Creates a slice allocation using the given allocator, initialized to the
+    /// items of the passed iterator, whose reported length is used as the
+    /// slice length. In case of allocation error or overflow calculating the
+    /// total size, `Err` is returned. If the iterator yields fewer items than
+    /// its reported length, the items already yielded are dropped, the
+    /// allocation is freed and `Err` is returned. Extra items are not consumed.
+    pub fn try_from_exact_iter_in<I>(
+        iter: I,
+        alloc: A,
+    ) -> Result<Self, FromIterErr>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let mut iter = iter.into_iter();
+        let len = iter.len();
+        UninitAlloc::try_new_slice_in(len, alloc)?.try_init_with(|found| {
+            iter.next().ok_or(FromIterErr::TooShort { len, found })
+        })
+    }
+}
+
+impl<T> OwnedAlloc<[T]>
+where
+    T: Copy,
+{
+    /// Creates a slice allocation initialized to a copy of the passed slice.
+    /// In case of allocation error, the handler registered via stdlib is
+    /// called. In case of overflow calculating the total size, the function
+    /// panics.
+    pub fn copy_from_slice(slice: &[T]) -> Self {
+        Self::copy_from_slice_in(slice, Global)
+    }
+}
+
+impl<T, A> OwnedAlloc<[T], A>
+where
+    T: Clone,
+    A: Allocator,
+{
+    /// Creates a slice allocation using the given allocator, initialized to
+    /// clones of the elements of the passed slice. In case of allocation error,
+    /// the handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, the function panics.
+    pub fn from_slice_in(slice: &[T], alloc: A) -> Self {
+        UninitAlloc::new_slice_in(slice.len(), alloc)
+            .init_with(|i| slice[i].clone())
+    }
+
+    /// Creates a slice allocation of `len` elements using the given allocator,
+    /// initialized to clones of the passed element. In case of allocation
+    /// error, the handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, the function panics.
+    pub fn from_elem_in(elem: T, len: usize, alloc: A) -> Self {
+        let mut elem = Some(elem);
+        UninitAlloc::new_slice_in(len, alloc).init_with(|i| {
+            if i + 1 == len {
+                elem.take().unwrap()
+            } else {
+                elem.clone().unwrap()
+            }
+        })
+    }
+}
+
+impl<T, A> OwnedAlloc<[T], A>
+where
+    T: Copy,
+    A: Allocator,
+{
+    /// Creates a slice allocation using the given allocator, initialized to a
+    /// copy of the passed slice. In case of allocation error, the handler
+    /// registered via stdlib is called. In case of overflow calculating the
+    /// total size, the function panics.
+    pub fn copy_from_slice_in(slice: &[T], alloc: A) -> Self {
+        let uninit = UninitAlloc::new_slice_in(slice.len(), alloc);
+        let (nnptr, alloc) = uninit.into_raw_with_alloc();
+        unsafe {
+            slice
+                .as_ptr()
+                .copy_to_nonoverlapping(nnptr.as_ptr() as *mut T, slice.len());
+            Self::from_raw_in(nnptr, alloc)
+        }
+    }
+}
+
+impl OwnedAlloc<str> {
+    /// Creates a string allocation initialized to a copy of the passed string.
+    /// In case of allocation error, the handler registered via stdlib is
+    /// called.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(string: &str) -> Self {
+        Self::from_str_in(string, Global)
+    }
+
+    /// Converts the plain old standard library `String` into an owned
+    /// allocation. The spare capacity of the `String` is freed.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because there are no guarantees that `String`
+    /// and `OwnedAlloc` allocate in the same way. They probably do in the Rust
+    /// version you are using, but there are no future guarantees.
+    pub unsafe fn from_string(string: String) -> Self {
+        Self::from_box(string.into_boxed_str())
+    }
+
+    /// Converts the owned allocation into a plain old standard library
+    /// `String`.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because there are no guarantees that `String`
+    /// and `OwnedAlloc` allocate in the same way. They probably do in the Rust
+    /// version you are using, but there are no future guarantees.
+    pub unsafe fn into_string(self) -> String {
+        self.into_box().into_string()
+    }
+}
+
+impl<A> OwnedAlloc<str, A>
+where
+    A: Allocator,
+{
+    /// Creates a string allocation using the given allocator, initialized to a
+    /// copy of the passed string. In case of allocation error, the handler
+    /// registered via stdlib is called.
+    pub fn from_str_in(string: &str, alloc: A) -> Self {
+        let bytes = OwnedAlloc::copy_from_slice_in(string.as_bytes(), alloc);
+        unsafe { Self::from_utf8_unchecked(bytes) }
+    }
+
+    /// Converts an allocation of bytes into a string allocation, checking that
+    /// the bytes are valid UTF-8. No allocation is performed. In case the bytes
+    /// are not valid UTF-8, `Err` is returned, from which the bytes can be
+    /// recovered.
+    pub fn from_utf8(
+        bytes: OwnedAlloc<[u8], A>,
+    ) -> Result<Self, FromUtf8Err<A>> {
+        match str::from_utf8(&bytes) {
+            Ok(_) => Ok(unsafe { Self::from_utf8_unchecked(bytes) }),
+            Err(error) => Err(FromUtf8Err::new(bytes, error)),
+        }
+    }
+
+    /// Converts an allocation of bytes into a string allocation without
+    /// checking that the bytes are valid UTF-8. No allocation is performed.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because passing bytes which are not valid
+    /// UTF-8 leads to undefined behaviour.
+    pub unsafe fn from_utf8_unchecked(bytes: OwnedAlloc<[u8], A>) -> Self {
+        let (nnptr, alloc) = bytes.into_raw_with_alloc();
+        let nnptr = NonNull::new_unchecked(nnptr.as_ptr() as *mut str);
+        Self::from_raw_in(nnptr, alloc)
+    }
+
+    /// Converts the string allocation into an allocation of its bytes. No
+    /// allocation is performed.
+    pub fn into_bytes(self) -> OwnedAlloc<[u8], A> {
+        let (nnptr, alloc) = self.into_raw_with_alloc();
+        unsafe {
+            let nnptr = NonNull::new_unchecked(nnptr.as_ptr() as *mut [u8]);
+            OwnedAlloc::from_raw_in(nnptr, alloc)
+        }
+    }
+}
+
+macro_rules! impl_downcast {
+    ($($any:ty),*) => {
+        $(
+            impl<A> OwnedAlloc<$any, A>
+            where
+                A: Allocator,
+            {
+                /// Attempts to downcast the allocation to a concrete type. No
+                /// allocation is performed. In case the stored value is not a
+                /// `U`, the allocation is given back as `Err`.
+                pub fn downcast<U>(self) -> Result<OwnedAlloc<U, A>, Self>
+                where
+                    U: Any,
+                {
+                    if self.is::<U>() {
+                        let (nnptr, alloc) = self.into_raw_with_alloc();
+                        let nnptr = nnptr.cast::<U>();
+                        Ok(unsafe { OwnedAlloc::from_raw_in(nnptr, alloc) })
+                    } else {
+                        Err(self)
+                    }
+                }
+            }
+        )*
+    };
 }
 
+impl_downcast!(dyn Any, dyn Any + Send, dyn Any + Send + Sync);
+
 impl<T> OwnedAlloc<T>
 where
     T: ?Sized,
@@ -55,7 +440,7 @@ where
     /// undefined behaviour. Passing a pointer to uninitialized memory is also
     /// undefined behaviour.
     pub unsafe fn from_raw(nnptr: NonNull<T>) -> Self {
-        Self { nnptr, _marker: PhantomData }
+        Self::from_raw_in(nnptr, Global)
     }
 
     /// Converts the plain old standard library `Box` into an owned allocation.
@@ -68,63 +453,128 @@ where
         Self::from_raw(NonNull::new_unchecked(Box::into_raw(boxed)))
     }
 
+    /// Converts the owned allocation into a plain old standard library `Box`.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because there are no guarantees that `Box` and
+    /// `OwnedAlloc` allocate in the same way. They probably do in the Rust
+    /// version you are using, but there are no future guarantees.
+    pub unsafe fn into_box(self) -> Box<T> {
+        Box::from_raw(self.into_raw().as_ptr())
+    }
+}
+
+impl<T, A> OwnedAlloc<T, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+    /// Recreate the `OwnedAlloc` from a raw non-null pointer and the allocator
+    /// which allocated it.
+    ///
+    /// # Safety
+    /// This functions is `unsafe` because passing the wrong pointer leads to
+    /// undefined behaviour. Passing a pointer to uninitialized memory is also
+    /// undefined behaviour, and so is passing an allocator other than the one
+    /// used to allocate the pointer.
+    pub unsafe fn from_raw_in(nnptr: NonNull<T>, alloc: A) -> Self {
+        Self { nnptr, alloc, _marker: PhantomData }
+    }
+
     /// Returns the raw non-null pointer of the allocation.
     pub fn raw(&self) -> NonNull<T> {
         self.nnptr
     }
 
+    /// Returns a reference to the allocator backing this allocation.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     /// "Forgets" dropping both the allocation and its content and returns its
-    /// raw non-null pointer.
+    /// raw non-null pointer. The allocator is forgotten as well.
     pub fn into_raw(self) -> NonNull<T> {
         let nnptr = self.nnptr;
         mem::forget(self);
         nnptr
     }
 
-    /// Converts the owned allocation into a plain old standard library `Box`.
-    ///
-    /// # Safety
-    /// This function is `unsafe` because there are no guarantees that `Box` and
-    /// `OwnedAlloc` allocate in the same way. They probably do in the Rust
-    /// version you are using, but there are no future guarantees.
-    pub unsafe fn into_box(self) -> Box<T> {
-        Box::from_raw(self.into_raw().as_ptr())
+    /// "Forgets" dropping both the allocation and its content and returns its
+    /// raw non-null pointer together with the allocator which allocated it.
+    pub fn into_raw_with_alloc(self) -> (NonNull<T>, A) {
+        let this = ManuallyDrop::new(self);
+        (this.nnptr, unsafe { ptr::read(&this.alloc) })
     }
 
     /// Drops the memory and returns the allocation now considered
     /// uninitialized.
-    pub fn drop_in_place(self) -> UninitAlloc<T> {
+    pub fn drop_in_place(self) -> UninitAlloc<T, A> {
+        let (nnptr, alloc) = self.into_raw_with_alloc();
         unsafe {
-            self.nnptr.as_ptr().drop_in_place();
-            UninitAlloc::from_raw(self.into_raw())
+            nnptr.as_ptr().drop_in_place();
+            UninitAlloc::from_raw_in(nnptr, alloc)
         }
     }
 
     /// "Forgets" about dropping the inner value and returns an uninitialized
     /// allocation.
-    pub fn forget_inner(self) -> UninitAlloc<T> {
-        unsafe { UninitAlloc::from_raw(self.into_raw()) }
+    pub fn forget_inner(self) -> UninitAlloc<T, A> {
+        let (nnptr, alloc) = self.into_raw_with_alloc();
+        unsafe { UninitAlloc::from_raw_in(nnptr, alloc) }
+    }
+
+    /// Swaps the allocations of `self` and `other`, together with their
+    /// allocators. Only the pointers are swapped; the values are not moved.
+    pub fn swap(&mut self, other: &mut Self) {
+        mem::swap(self, other)
+    }
+
+    /// Pins the allocation. The value stays at its current address, so this
+    /// does not move it.
+    ///
+    /// The allocator must live for `'static`. Otherwise, the pinned allocation
+    /// could be forgotten and its memory reused once the allocator is gone or
+    /// reset, without the value ever being dropped:
+    ///
+    /// ```compile_fail
+    /// use std::marker::PhantomPinned;
+    /// use tux_owned_alloc::{Arena, OwnedAlloc};
+    ///
+    /// let arena = Arena::new();
+    /// let pinned = OwnedAlloc::new_in(PhantomPinned, &arena).into_pin();
+    /// ```
+    pub fn into_pin(self) -> Pin<Self>
+    where
+        A: 'static,
+    {
+        // The value lives behind the pointer, and without `Unpin` it can only
+        // be reached through `Pin`, which never hands out the allocation. The
+        // allocator lives for `'static`, so the memory cannot be reused before
+        // the value is dropped, even if the allocation is forgotten.
+        unsafe { Pin::new_unchecked(self) }
     }
 }
 
-impl<T> Drop for OwnedAlloc<T>
+impl<T, A> Drop for OwnedAlloc<T, A>
 where
     T: ?Sized,
+    A: Allocator,
 {
     fn drop(&mut self) {
         unsafe {
             let layout = Layout::for_value(self.nnptr.as_ref());
             self.nnptr.as_ptr().drop_in_place();
             if layout.size() != 0 {
-                dealloc(self.nnptr.cast().as_ptr(), layout);
+                self.alloc.dealloc(self.nnptr.cast(), layout);
             }
         }
     }
 }
 
-impl<T> Deref for OwnedAlloc<T>
+impl<T, A> Deref for OwnedAlloc<T, A>
 where
     T: ?Sized,
+    A: Allocator,
 {
     type Target = T;
 
@@ -133,30 +583,300 @@ where
     }
 }
 
-impl<T> DerefMut for OwnedAlloc<T>
+impl<T, A> DerefMut for OwnedAlloc<T, A>
 where
     T: ?Sized,
+    A: Allocator,
 {
     fn deref_mut(&mut self) -> &mut T {
         unsafe { self.nnptr.as_mut() }
     }
 }
 
-impl<T> fmt::Debug for OwnedAlloc<T>
+impl<T, A> AsRef<T> for OwnedAlloc<T, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T, A> AsMut<T> for OwnedAlloc<T, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T, A> Borrow<T> for OwnedAlloc<T, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T, A> BorrowMut<T> for OwnedAlloc<T, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T, A> PartialEq for OwnedAlloc<T, A>
+where
+    T: ?Sized + PartialEq,
+    A: Allocator,
+{
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T, A> Eq for OwnedAlloc<T, A>
+where
+    T: ?Sized + Eq,
+    A: Allocator,
+{
+}
+
+impl<T, A> PartialOrd for OwnedAlloc<T, A>
+where
+    T: ?Sized + PartialOrd,
+    A: Allocator,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+
+    fn lt(&self, other: &Self) -> bool {
+        **self < **other
+    }
+
+    fn le(&self, other: &Self) -> bool {
+        **self <= **other
+    }
+
+    fn gt(&self, other: &Self) -> bool {
+        **self > **other
+    }
+
+    fn ge(&self, other: &Self) -> bool {
+        **self >= **other
+    }
+}
+
+impl<T, A> Ord for OwnedAlloc<T, A>
+where
+    T: ?Sized + Ord,
+    A: Allocator,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T, A> Hash for OwnedAlloc<T, A>
+where
+    T: ?Sized + Hash,
+    A: Allocator,
+{
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        (**self).hash(state)
+    }
+}
+
+impl<T, A> fmt::Debug for OwnedAlloc<T, A>
+where
+    T: ?Sized + fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, fmtr)
+    }
+}
+
+impl<T, A> fmt::Display for OwnedAlloc<T, A>
+where
+    T: ?Sized + fmt::Display,
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&**self, fmtr)
+    }
+}
+
+impl<T, A> fmt::Pointer for OwnedAlloc<T, A>
 where
     T: ?Sized,
+    A: Allocator,
 {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmtr, "{:?}", self.nnptr)
+        fmt::Pointer::fmt(&self.nnptr, fmtr)
+    }
+}
+
+impl<T> Default for OwnedAlloc<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Default for OwnedAlloc<[T]> {
+    fn default() -> Self {
+        Self::from_iter(iter::empty())
+    }
+}
+
+impl Default for OwnedAlloc<str> {
+    fn default() -> Self {
+        Self::from_str("")
+    }
+}
+
+impl<I, A> Iterator for OwnedAlloc<I, A>
+where
+    I: ?Sized + Iterator,
+    A: Allocator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        (**self).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<I::Item> {
+        (**self).nth(n)
+    }
+}
+
+impl<I, A> DoubleEndedIterator for OwnedAlloc<I, A>
+where
+    I: ?Sized + DoubleEndedIterator,
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<I::Item> {
+        (**self).next_back()
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<I::Item> {
+        (**self).nth_back(n)
+    }
+}
+
+impl<I, A> ExactSizeIterator for OwnedAlloc<I, A>
+where
+    I: ?Sized + ExactSizeIterator,
+    A: Allocator,
+{
+    fn len(&self) -> usize {
+        (**self).len()
     }
 }
 
-impl<T> Clone for OwnedAlloc<T>
+impl<I, A> FusedIterator for OwnedAlloc<I, A>
+where
+    I: ?Sized + FusedIterator,
+    A: Allocator,
+{
+}
+
+impl<F, A> Future for OwnedAlloc<F, A>
+where
+    F: ?Sized + Future + Unpin,
+    A: Allocator,
+{
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<F::Output> {
+        F::poll(Pin::new(&mut **self), cx)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<Args, F, A> FnOnce<Args> for OwnedAlloc<F, A>
+where
+    Args: Tuple,
+    F: ?Sized + FnMut<Args>,
+    A: Allocator,
+{
+    type Output = F::Output;
+
+    extern "rust-call" fn call_once(mut self, args: Args) -> F::Output {
+        (*self).call_mut(args)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<Args, F, A> FnMut<Args> for OwnedAlloc<F, A>
+where
+    Args: Tuple,
+    F: ?Sized + FnMut<Args>,
+    A: Allocator,
+{
+    extern "rust-call" fn call_mut(&mut self, args: Args) -> F::Output {
+        (**self).call_mut(args)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<Args, F, A> Fn<Args> for OwnedAlloc<F, A>
+where
+    Args: Tuple,
+    F: ?Sized + Fn<Args>,
+    A: Allocator,
+{
+    extern "rust-call" fn call(&self, args: Args) -> F::Output {
+        (**self).call(args)
+    }
+}
+
+impl<T, A> Clone for OwnedAlloc<T, A>
 where
     T: Clone,
+    A: Allocator + Clone,
 {
     fn clone(&self) -> Self {
-        Self::new((**self).clone())
+        Self::new_in((**self).clone(), self.alloc.clone())
+    }
+}
+
+impl<T, A> Clone for OwnedAlloc<[T], A>
+where
+    T: Clone,
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> Self {
+        Self::from_slice_in(self, self.alloc.clone())
+    }
+}
+
+impl<A> Clone for OwnedAlloc<str, A>
+where
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> Self {
+        Self::from_str_in(self, self.alloc.clone())
     }
 }
 
@@ -166,12 +886,106 @@ impl<T> From<T> for OwnedAlloc<T> {
     }
 }
 
-unsafe impl<T> Send for OwnedAlloc<T> where T: ?Sized + Send {}
-unsafe impl<T> Sync for OwnedAlloc<T> where T: ?Sized + Sync {}
+impl<T> FromIterator<T> for OwnedAlloc<[T]> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut vec = iter.into_iter().collect::<OwnedVec<T>>();
+        vec.shrink_to_fit();
+        let (raw, _) = vec.into_raw_parts();
+        unsafe { UninitAlloc::from(raw).assume_init() }
+    }
+}
+
+impl From<&str> for OwnedAlloc<str> {
+    fn from(string: &str) -> Self {
+        Self::from_str(string)
+    }
+}
+
+impl str::FromStr for OwnedAlloc<str> {
+    type Err = Infallible;
+
+    fn from_str(string: &str) -> Result<Self, Infallible> {
+        Ok(Self::from_str(string))
+    }
+}
+
+impl<T, A> From<OwnedAlloc<T, A>> for Pin<OwnedAlloc<T, A>>
+where
+    T: ?Sized,
+    A: Allocator + 'static,
+{
+    fn from(alloc: OwnedAlloc<T, A>) -> Self {
+        alloc.into_pin()
+    }
+}
+
+impl<A> From<OwnedAlloc<str, A>> for String
+where
+    A: Allocator,
+{
+    fn from(alloc: OwnedAlloc<str, A>) -> Self {
+        String::from(&*alloc)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T, U, A> CoerceUnsized<OwnedAlloc<U, A>> for OwnedAlloc<T, A>
+where
+    T: ?Sized + Unsize<U>,
+    U: ?Sized,
+    A: Allocator,
+{
+}
+
+unsafe impl<T, A> Send for OwnedAlloc<T, A>
+where
+    T: ?Sized + Send,
+    A: Allocator + Send,
+{
+}
+
+unsafe impl<T, A> Sync for OwnedAlloc<T, A>
+where
+    T: ?Sized + Sync,
+    A: Allocator + Sync,
+{
+}
+
+impl<T, A> Unpin for OwnedAlloc<T, A>
+where
+    T: ?Sized,
+    A: Allocator,
+{
+}
 
 #[cfg(test)]
 mod test {
-    use super::OwnedAlloc;
+    extern crate std;
+
+    use alloc::{
+        collections::BTreeMap,
+        format,
+        rc::Rc,
+        string::String,
+        vec,
+        vec::Vec,
+    };
+    use super::{
+        super::{CountingAlloc, FromIterErr, Global, UninitAlloc},
+        OwnedAlloc,
+    };
+    use core::{
+        any::Any,
+        future::{self, Future},
+        marker::PhantomPinned,
+        pin::Pin,
+        ptr::NonNull,
+        task::{Context, Poll, Waker},
+    };
+    use std::collections::HashMap;
 
     #[test]
     fn inner_eq() {
@@ -198,4 +1012,225 @@ mod test {
         let raw = unsafe { OwnedAlloc::from_box(boxed) };
         assert_eq!(*raw, [5; 32]);
     }
+
+    #[test]
+    fn zeroed() {
+        let alloc = OwnedAlloc::<(u8, [f64; 8], bool)>::new_zeroed();
+        assert_eq!(*alloc, (0, [0.0; 8], false));
+    }
+
+    #[test]
+    fn slices() {
+        let strings = [String::from("a"), String::from("b")];
+        assert_eq!(*OwnedAlloc::from_slice(&strings), strings);
+        assert_eq!(*OwnedAlloc::copy_from_slice(&[1, 2, 3]), [1, 2, 3]);
+        assert_eq!(*OwnedAlloc::from_elem(String::from("c"), 3), ["c"; 3]);
+        assert!(OwnedAlloc::from_elem(String::new(), 0).is_empty());
+    }
+
+    #[test]
+    fn from_exact_iter() {
+        let alloc = OwnedAlloc::try_from_exact_iter(vec![1, 2, 3]).unwrap();
+        assert_eq!(*alloc, [1, 2, 3]);
+
+        let counter = Rc::new(());
+        let short = ShortIter { counter: counter.clone(), left: 2 };
+        match OwnedAlloc::try_from_exact_iter(short) {
+            Err(FromIterErr::TooShort { len: 4, found: 2 }) => (),
+            res => panic!("unexpected {:?}", res),
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn strings() {
+        let alloc = OwnedAlloc::from_str("identifier");
+        assert_eq!(&*alloc, "identifier");
+        assert_eq!(String::from(alloc), "identifier");
+
+        let string = unsafe { OwnedAlloc::<str>::from("ñ").into_string() };
+        assert_eq!(string, "ñ");
+        let alloc = unsafe { OwnedAlloc::from_string(string) };
+        assert_eq!(&*alloc, "ñ");
+        assert_eq!(&*"parsed".parse::<OwnedAlloc<str>>().unwrap(), "parsed");
+    }
+
+    #[test]
+    fn from_utf8() {
+        let bytes = OwnedAlloc::copy_from_slice(b"valid");
+        assert_eq!(&*OwnedAlloc::from_utf8(bytes).unwrap(), "valid");
+
+        let bytes = OwnedAlloc::from_slice(&[b'a', 0xFF]);
+        let err = OwnedAlloc::from_utf8(bytes).unwrap_err();
+        assert_eq!(err.utf8_error().valid_up_to(), 1);
+        assert_eq!(*err.into_bytes(), vec![b'a', 0xFF]);
+    }
+
+    #[test]
+    fn unsize_and_downcast() {
+        let alloc = unsize!(OwnedAlloc::new([1, 2, 3]), [i32]);
+        assert_eq!(alloc.len(), 3);
+
+        let alloc = unsize!(OwnedAlloc::new(String::from("any")), dyn Any);
+        let alloc = alloc.downcast::<u32>().unwrap_err();
+        assert_eq!(*alloc.downcast::<String>().unwrap(), "any");
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn coerce_unsized() {
+        let alloc: OwnedAlloc<dyn Any + Send> = OwnedAlloc::new(5u8);
+        assert_eq!(*alloc.downcast::<u8>().unwrap(), 5);
+    }
+
+    #[test]
+    fn value_traits() {
+        let small = OwnedAlloc::new(1);
+        let big = OwnedAlloc::new(2);
+        assert!(small < big && small != big);
+        assert_eq!(small, OwnedAlloc::new(1));
+        assert_eq!(small.cmp(&big), core::cmp::Ordering::Less);
+
+        assert_eq!(format!("{:?} {}", small, big), "1 2");
+        assert_eq!(format!("{:?}", OwnedAlloc::<str>::from("str")), "\"str\"");
+        assert_eq!(*OwnedAlloc::<u8>::default(), 0);
+        assert_eq!(OwnedAlloc::<[u8]>::default().len(), 0);
+        assert_eq!(&*OwnedAlloc::<str>::default(), "");
+
+        let mut map = HashMap::new();
+        map.insert(OwnedAlloc::<str>::from("key"), 1);
+        assert_eq!(map.get("key"), Some(&1));
+
+        let mut map = BTreeMap::new();
+        map.insert(OwnedAlloc::new(String::from("b")), 2);
+        map.insert(OwnedAlloc::new(String::from("a")), 1);
+        assert_eq!(map.keys().next().map(|key| key.as_str()), Some("a"));
+
+        let mut alloc = OwnedAlloc::new(5);
+        *alloc.as_mut() += 1;
+        assert_eq!(*alloc.as_ref(), 6);
+    }
+
+    #[test]
+    fn clone_unsized() {
+        let counter = CountingAlloc::new();
+        let strings = [String::from("a"), String::from("b")];
+        let slice = OwnedAlloc::from_slice_in(&strings, &counter);
+        let cloned = slice.clone();
+        assert_eq!(&*cloned, &strings);
+        assert_ne!(cloned.raw(), slice.raw());
+
+        let string = OwnedAlloc::from_str_in("string", &counter);
+        assert_eq!(&*string.clone(), "string");
+        assert_eq!(counter.stats().allocs, 4);
+
+        drop((slice, cloned, string));
+        assert_eq!(counter.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn forwarding() {
+        let mut iter = OwnedAlloc::new(vec![1, 2, 3, 4].into_iter());
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next_back(), Some(4));
+        assert_eq!(iter.collect::<Vec<_>>(), [1, 2, 3]);
+
+        let mut fut = OwnedAlloc::new(future::ready(7));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(7));
+
+        let slice = (0 .. 5).map(|i| i * 2).collect::<OwnedAlloc<[_]>>();
+        assert_eq!(&*slice, &[0, 2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn replace_in_place() {
+        let counter = CountingAlloc::new();
+        let mut alloc = OwnedAlloc::new_in(String::from("a"), &counter);
+        let addr = alloc.raw();
+        assert_eq!(alloc.replace(String::from("b")), "a");
+        assert_eq!(alloc.take(), "b");
+        assert!(alloc.is_empty());
+        assert_eq!((alloc.raw(), counter.stats().allocs), (addr, 1));
+
+        let mut other = OwnedAlloc::new_in(String::from("c"), &counter);
+        let other_addr = other.raw();
+        alloc.swap(&mut other);
+        assert_eq!((alloc.as_str(), alloc.raw()), ("c", other_addr));
+        assert_eq!((other.as_str(), other.raw()), ("", addr));
+
+        let old = alloc.set_from(UninitAlloc::new_in(&counter));
+        assert_eq!((old.raw(), alloc.as_str()), (other_addr, "c"));
+        drop((old, alloc, other));
+        assert_eq!(counter.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn pinning() {
+        fn assert_unpin<T: Unpin>(_: &T) {}
+
+        let mut fut = OwnedAlloc::pin(async { future::ready(3).await * 2 });
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(6));
+
+        let unpin = OwnedAlloc::new(PhantomPinned);
+        assert_unpin(&unpin);
+        let addr = unpin.raw();
+        let pinned = Pin::from(unpin);
+        assert_eq!(NonNull::from(&*pinned), addr);
+
+        let pinned = OwnedAlloc::try_pin_in(7, Global).unwrap();
+        assert_eq!(*Pin::into_inner(pinned), 7);
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn fn_traits() {
+        fn call<F: FnOnce(u8) -> u8>(fun: F) -> u8 {
+            fun(2)
+        }
+
+        fn call_twice<F: FnMut()>(mut fun: F) {
+            fun();
+            fun();
+        }
+
+        let mut count = 0;
+        call_twice(OwnedAlloc::new(|| count += 1));
+        assert_eq!(count, 2);
+
+        let double: OwnedAlloc<dyn Fn(u8) -> u8> =
+            OwnedAlloc::new(|x: u8| x * 2);
+        assert_eq!(double(3), 6);
+        assert_eq!(call(double), 4);
+    }
+
+    #[test]
+    fn with_allocator() {
+        let alloc = OwnedAlloc::new_in(String::from("in"), &Global);
+        let (val, uninit) = alloc.move_inner();
+        assert_eq!(val, "in");
+        assert_eq!(*uninit.init(String::from("out")), "out");
+    }
+
+    /// Reports a length of `4` but only yields `left` items.
+    struct ShortIter {
+        counter: Rc<()>,
+        left: usize,
+    }
+
+    impl Iterator for ShortIter {
+        type Item = Rc<()>;
+
+        fn next(&mut self) -> Option<Rc<()>> {
+            self.left = self.left.checked_sub(1)?;
+            Some(self.counter.clone())
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (4, Some(4))
+        }
+    }
+
+    impl ExactSizeIterator for ShortIter {}
 }
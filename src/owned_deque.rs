@@ -0,0 +1,536 @@
+use super::{Allocator, Global, RawVec, RawVecErr};
+use core::{
+    fmt,
+    iter::Chain,
+    ptr,
+    slice::{self, Iter},
+};
+
+/// A double-ended queue of `T`s implemented as a ring buffer on top of a
+/// `RawVec`. Elements can be pushed and popped at both ends in constant time.
+/// Unlike `VecDeque`, growth can be fallible (see `try_push_back`). The
+/// memory is obtained from the allocator `A`, which is the global allocator by
+/// default.
+///
+/// ```rust
+/// use tux_owned_alloc::OwnedDeque;
+///
+/// let mut deque = OwnedDeque::with_capacity(4);
+/// deque.push_back(2);
+/// deque.push_back(3);
+/// deque.push_front(1);
+/// assert_eq!(deque.pop_back(), Some(3));
+/// deque.push_back(4);
+/// deque.push_back(5);
+///
+/// let (front, back) = deque.as_slices();
+/// assert_eq!([front, back].concat(), [1, 2, 4, 5]);
+/// assert_eq!(deque.iter().copied().collect::<Vec<_>>(), [1, 2, 4, 5]);
+/// ```
+pub struct OwnedDeque<T, A = Global>
+where
+    A: Allocator,
+{
+    raw: RawVec<T, A>,
+    head: usize,
+    len: usize,
+}
+
+impl<T> OwnedDeque<T> {
+    /// Creates a new empty deque. No allocation is performed.
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Creates a new empty deque with room for `cap` elements. In case of
+    /// allocation error, the handler registered via stdlib is called. In case
+    /// of overflow calculating the total size, the function panics.
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    /// Creates a new empty deque with room for `cap` elements. In case of
+    /// allocation error or overflow calculating the total size, `Err` is
+    /// returned.
+    pub fn try_with_capacity(cap: usize) -> Result<Self, RawVecErr> {
+        Self::try_with_capacity_in(cap, Global)
+    }
+}
+
+impl<T> Default for OwnedDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A> OwnedDeque<T, A>
+where
+    A: Allocator,
+{
+    /// Creates a new empty deque using the given allocator. No allocation is
+    /// performed.
+    pub fn new_in(alloc: A) -> Self {
+        Self::from(RawVec::new_in(alloc))
+    }
+
+    /// Creates a new empty deque with room for `cap` elements using the given
+    /// allocator. In case of allocation error, the handler registered via
+    /// stdlib is called. In case of overflow calculating the total size, the
+    /// function panics.
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        Self::from(RawVec::with_capacity_in(cap, alloc))
+    }
+
+    /// Creates a new empty deque with room for `cap` elements using the given
+    /// allocator. In case of allocation error or overflow calculating the
+    /// total size, `Err` is returned.
+    pub fn try_with_capacity_in(
+        cap: usize,
+        alloc: A,
+    ) -> Result<Self, RawVecErr> {
+        RawVec::try_with_capacity_in(cap, alloc).map(Self::from)
+    }
+
+    /// The number of elements in the deque.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Tests if the deque has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements the deque has room for without growing.
+    pub fn capacity(&self) -> usize {
+        self.raw.cap()
+    }
+
+    /// Returns a reference to the allocator backing this deque.
+    pub fn allocator(&self) -> &A {
+        self.raw.allocator()
+    }
+
+    /// Ensures the deque has room for at least `additional` more elements,
+    /// growing it amortizedly if needed. In case of allocation error, the
+    /// handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, the function panics.
+    pub fn reserve(&mut self, additional: usize) {
+        let old_cap = self.raw.cap();
+        self.raw.reserve(self.len, additional);
+        self.relinearize(old_cap);
+    }
+
+    /// Ensures the deque has room for at least `additional` more elements,
+    /// growing it amortizedly if needed. In case of allocation error or
+    /// overflow calculating the total size, `Err` is returned and the deque
+    /// is untouched.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), RawVecErr> {
+        let old_cap = self.raw.cap();
+        self.raw.try_reserve(self.len, additional)?;
+        self.relinearize(old_cap);
+        Ok(())
+    }
+
+    /// Ensures the deque has room for exactly `additional` more elements. In
+    /// case of allocation error, the handler registered via stdlib is called.
+    /// In case of overflow calculating the total size, the function panics.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let old_cap = self.raw.cap();
+        self.raw.reserve_exact(self.len, additional);
+        self.relinearize(old_cap);
+    }
+
+    /// Appends an element to the back of the deque. In case of allocation
+    /// error, the handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, the function panics.
+    pub fn push_back(&mut self, val: T) {
+        if self.len == self.raw.cap() {
+            self.reserve(1);
+        }
+        unsafe { self.write_back(val) }
+    }
+
+    /// Appends an element to the back of the deque. In case of allocation
+    /// error or overflow calculating the total size, `Err` is returned, the
+    /// passed element is dropped and the deque is untouched.
+    pub fn try_push_back(&mut self, val: T) -> Result<(), RawVecErr> {
+        if self.len == self.raw.cap() {
+            self.try_reserve(1)?;
+        }
+        unsafe { self.write_back(val) };
+        Ok(())
+    }
+
+    /// Prepends an element to the front of the deque. In case of allocation
+    /// error, the handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, the function panics.
+    pub fn push_front(&mut self, val: T) {
+        if self.len == self.raw.cap() {
+            self.reserve(1);
+        }
+        unsafe { self.write_front(val) }
+    }
+
+    /// Prepends an element to the front of the deque. In case of allocation
+    /// error or overflow calculating the total size, `Err` is returned, the
+    /// passed element is dropped and the deque is untouched.
+    pub fn try_push_front(&mut self, val: T) -> Result<(), RawVecErr> {
+        if self.len == self.raw.cap() {
+            self.try_reserve(1)?;
+        }
+        unsafe { self.write_front(val) };
+        Ok(())
+    }
+
+    /// Removes the last element of the deque and returns it, or `None` if the
+    /// deque is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            let index = self.physical(self.len);
+            Some(unsafe { self.ptr().add(index).read() })
+        }
+    }
+
+    /// Removes the first element of the deque and returns it, or `None` if
+    /// the deque is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            let index = self.head;
+            self.head = self.physical(1);
+            self.len -= 1;
+            Some(unsafe { self.ptr().add(index).read() })
+        }
+    }
+
+    /// Returns a reference to the element at position `index` counting from
+    /// the front, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            Some(unsafe { &*self.ptr().add(self.physical(index)) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the element at position `index`
+    /// counting from the front, or `None` if it is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            Some(unsafe { &mut *self.ptr().add(self.physical(index)) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a reference to the first element, or `None` if the deque is
+    /// empty.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a reference to the last element, or `None` if the deque is
+    /// empty.
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Returns the elements as two slices which, concatenated, are the
+    /// contents of the deque in order. The second slice is empty unless the
+    /// elements wrap around the end of the buffer.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.slice_ranges();
+        unsafe {
+            (
+                slice::from_raw_parts(self.ptr().add(front.0), front.1),
+                slice::from_raw_parts(self.ptr(), back),
+            )
+        }
+    }
+
+    /// Returns the elements as two mutable slices which, concatenated, are
+    /// the contents of the deque in order. The second slice is empty unless
+    /// the elements wrap around the end of the buffer.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.slice_ranges();
+        unsafe {
+            (
+                slice::from_raw_parts_mut(self.ptr().add(front.0), front.1),
+                slice::from_raw_parts_mut(self.ptr(), back),
+            )
+        }
+    }
+
+    /// Iterates over the elements from front to back.
+    pub fn iter(&self) -> Chain<Iter<'_, T>, Iter<'_, T>> {
+        let (front, back) = self.as_slices();
+        front.iter().chain(back.iter())
+    }
+
+    /// Drops all elements of the deque. The capacity is kept.
+    pub fn clear(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        let (front, back) = (front as *mut [T], back as *mut [T]);
+        self.head = 0;
+        self.len = 0;
+        unsafe { drop_slices(front, back) }
+    }
+
+    unsafe fn write_back(&mut self, val: T) {
+        let index = self.physical(self.len);
+        self.ptr().add(index).write(val);
+        self.len += 1;
+    }
+
+    unsafe fn write_front(&mut self, val: T) {
+        let cap = self.raw.cap();
+        self.head = if self.head == 0 { cap - 1 } else { self.head - 1 };
+        self.ptr().add(self.head).write(val);
+        self.len += 1;
+    }
+
+    /// Moves the wrapped part of the elements after the buffer grew from
+    /// `old_cap`, so they are contiguous modulo the new capacity again.
+    fn relinearize(&mut self, old_cap: usize) {
+        let new_cap = self.raw.cap();
+        if new_cap == old_cap || self.head + self.len <= old_cap {
+            return;
+        }
+
+        let head_len = old_cap - self.head;
+        let tail_len = self.len - head_len;
+        unsafe {
+            if tail_len <= new_cap - old_cap {
+                let dest = self.ptr().add(old_cap);
+                ptr::copy_nonoverlapping(self.ptr(), dest, tail_len);
+            } else {
+                let new_head = new_cap - head_len;
+                let src = self.ptr().add(self.head);
+                ptr::copy(src, self.ptr().add(new_head), head_len);
+                self.head = new_head;
+            }
+        }
+    }
+
+    /// The start and length of the first slice, and the length of the second
+    /// one, which always starts at `0`.
+    fn slice_ranges(&self) -> ((usize, usize), usize) {
+        let cap = self.raw.cap();
+        if self.len <= cap - self.head {
+            ((self.head, self.len), 0)
+        } else {
+            let head_len = cap - self.head;
+            ((self.head, head_len), self.len - head_len)
+        }
+    }
+
+    /// The index in the buffer of the element at position `index`, which must
+    /// be less than or equal to the capacity.
+    fn physical(&self, index: usize) -> usize {
+        let cap = self.raw.cap();
+        if index >= cap - self.head {
+            index - (cap - self.head)
+        } else {
+            self.head + index
+        }
+    }
+
+    fn ptr(&self) -> *mut T {
+        self.raw.raw().as_ptr()
+    }
+}
+
+/// Drops the two slices of a deque, dropping the second one even if dropping
+/// the first one panics.
+unsafe fn drop_slices<T>(front: *mut [T], back: *mut [T]) {
+    struct Dropper<T>(*mut [T]);
+
+    impl<T> Drop for Dropper<T> {
+        fn drop(&mut self) {
+            unsafe { self.0.drop_in_place() }
+        }
+    }
+
+    let _back = Dropper(back);
+    front.drop_in_place();
+}
+
+impl<T, A> Drop for OwnedDeque<T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        unsafe { drop_slices(front, back) }
+    }
+}
+
+impl<T, A> fmt::Debug for OwnedDeque<T, A>
+where
+    T: fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, A> Clone for OwnedDeque<T, A>
+where
+    T: Clone,
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> Self {
+        let mut deque =
+            Self::with_capacity_in(self.len, self.allocator().clone());
+        deque.extend(self.iter().cloned());
+        deque
+    }
+}
+
+impl<T, A> Extend<T> for OwnedDeque<T, A>
+where
+    A: Allocator,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for val in iter {
+            self.push_back(val);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for OwnedDeque<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T, A> From<RawVec<T, A>> for OwnedDeque<T, A>
+where
+    A: Allocator,
+{
+    fn from(raw: RawVec<T, A>) -> Self {
+        Self { raw, head: 0, len: 0 }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        super::{FailPolicy, FailingAlloc, RawVecErr},
+        OwnedDeque,
+    };
+    use alloc::{rc::Rc, vec::Vec};
+
+    fn contents<T: Clone>(deque: &OwnedDeque<T>) -> Vec<T> {
+        let (front, back) = deque.as_slices();
+        [front, back].concat()
+    }
+
+    #[test]
+    fn push_pop_both_ends() {
+        let mut deque = OwnedDeque::new();
+        assert_eq!((deque.pop_front(), deque.pop_back()), (None, None));
+
+        for i in 0 .. 5 {
+            deque.push_back(i);
+            deque.push_front(-i);
+        }
+        assert_eq!(contents(&deque), [-4, -3, -2, -1, 0, 0, 1, 2, 3, 4]);
+        assert_eq!((deque.front(), deque.back()), (Some(&-4), Some(&4)));
+
+        assert_eq!(deque.pop_front(), Some(-4));
+        assert_eq!(deque.pop_back(), Some(4));
+        *deque.get_mut(0).unwrap() = 30;
+        assert_eq!(deque.get(0), Some(&30));
+        assert_eq!(deque.get(8), None);
+        assert_eq!(deque.len(), 8);
+    }
+
+    #[test]
+    fn wraparound_and_growth() {
+        let mut deque = OwnedDeque::with_capacity(4);
+        deque.extend([0, 1, 2]);
+        deque.pop_front();
+        deque.pop_front();
+        deque.extend([3, 4, 5]);
+        assert_eq!(deque.capacity(), 4);
+        assert_eq!(deque.as_slices(), (&[2, 3][..], &[4, 5][..]));
+
+        deque.push_back(6);
+        assert_eq!(deque.capacity(), 8);
+        assert_eq!(contents(&deque), [2, 3, 4, 5, 6]);
+
+        let mut deque = OwnedDeque::with_capacity(3);
+        deque.extend([0, 1, 2]);
+        deque.pop_front();
+        deque.pop_front();
+        deque.extend([3, 4]);
+        deque.reserve_exact(1);
+        assert_eq!(deque.capacity(), 4);
+        assert_eq!(deque.as_slices(), (&[2][..], &[3, 4][..]));
+
+        deque.push_front(-1);
+        assert_eq!(contents(&deque), [-1, 2, 3, 4]);
+        assert_eq!(deque.iter().count(), 4);
+    }
+
+    #[test]
+    fn try_push_back_fails_untouched() {
+        let failing = FailingAlloc::new(FailPolicy::AboveBytes(8));
+        let mut deque = OwnedDeque::<u32, _>::new_in(&failing);
+
+        deque.try_push_back(1).unwrap();
+        deque.try_push_front(0).unwrap();
+        assert!(matches!(deque.try_push_back(2), Err(RawVecErr::Alloc(_))));
+        assert!(matches!(deque.try_push_front(2), Err(RawVecErr::Alloc(_))));
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), [0, 1]);
+    }
+
+    #[test]
+    fn drops_everything() {
+        let counter = Rc::new(());
+        let mut deque = OwnedDeque::with_capacity(4);
+        for _ in 0 .. 3 {
+            deque.push_front(counter.clone());
+        }
+        deque.push_back(counter.clone());
+        drop(deque.clone());
+        assert_eq!(Rc::strong_count(&counter), 5);
+
+        deque.pop_back();
+        deque.clear();
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        deque.extend((0 .. 10).map(|_| counter.clone()));
+        drop(deque);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut deque = OwnedDeque::new();
+        for _ in 0 .. 100 {
+            deque.push_front(());
+            deque.push_back(());
+        }
+        assert_eq!(deque.pop_front(), Some(()));
+        assert_eq!(deque.len(), 199);
+        assert_eq!(deque.iter().count(), 199);
+    }
+}
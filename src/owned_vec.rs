@@ -0,0 +1,701 @@
+use super::{Allocator, Global, RawVec, RawVecErr};
+use core::{
+    fmt,
+    iter::FusedIterator,
+    mem,
+    ops::{Bound, Deref, DerefMut, RangeBounds},
+    ptr, slice,
+};
+
+/// A growable vector of `T`s built on top of a `RawVec`. Unlike `RawVec`, it
+/// keeps track of how many elements are initialized, drops them when needed,
+/// and is safe to use, just like a `Vec`. The memory is obtained from the
+/// allocator `A`, which is the global allocator by default.
+///
+/// ```rust
+/// use tux_owned_alloc::OwnedVec;
+///
+/// let mut vec = OwnedVec::new();
+/// vec.extend(1 ..= 5);
+/// vec.retain(|&elem| elem % 2 == 1);
+/// vec.insert(1, 10);
+/// assert_eq!(&*vec, &[1, 10, 3, 5]);
+///
+/// let drained = vec.drain(1 .. 3).collect::<Vec<_>>();
+/// assert_eq!(drained, [10, 3]);
+/// assert_eq!(vec.pop(), Some(5));
+/// assert_eq!(&*vec, &[1]);
+/// ```
+pub struct OwnedVec<T, A = Global>
+where
+    A: Allocator,
+{
+    raw: RawVec<T, A>,
+    len: usize,
+}
+
+impl<T> OwnedVec<T> {
+    /// Creates a new empty vector. No allocation is performed.
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Creates a new empty vector with room for `cap` elements. In case of
+    /// allocation error, the handler registered via stdlib is called. In case
+    /// of overflow calculating the total size, the function panics.
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    /// Creates a new empty vector with room for `cap` elements. In case of
+    /// allocation error or overflow calculating the total size, `Err` is
+    /// returned.
+    pub fn try_with_capacity(cap: usize) -> Result<Self, RawVecErr> {
+        Self::try_with_capacity_in(cap, Global)
+    }
+}
+
+impl<T> Default for OwnedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A> OwnedVec<T, A>
+where
+    A: Allocator,
+{
+    /// Creates a new empty vector using the given allocator. No allocation is
+    /// performed.
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            raw: RawVec::new_in(alloc),
+            len: 0,
+        }
+    }
+
+    /// Creates a new empty vector with room for `cap` elements using the given
+    /// allocator. In case of allocation error, the handler registered via
+    /// stdlib is called. In case of overflow calculating the total size, the
+    /// function panics.
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        Self {
+            raw: RawVec::with_capacity_in(cap, alloc),
+            len: 0,
+        }
+    }
+
+    /// Creates a new empty vector with room for `cap` elements using the given
+    /// allocator. In case of allocation error or overflow calculating the
+    /// total size, `Err` is returned.
+    pub fn try_with_capacity_in(
+        cap: usize,
+        alloc: A,
+    ) -> Result<Self, RawVecErr> {
+        RawVec::try_with_capacity_in(cap, alloc).map(|raw| Self { raw, len: 0 })
+    }
+
+    /// Recreate the `OwnedVec` from a `RawVec` whose first `len` elements are
+    /// initialized.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because passing a length greater than the
+    /// capacity, or one including uninitialized elements, leads to undefined
+    /// behaviour.
+    pub unsafe fn from_raw_vec(raw: RawVec<T, A>, len: usize) -> Self {
+        Self { raw, len }
+    }
+
+    /// Drops the elements and converts the vector into its `RawVec`, now
+    /// uninitialized, and the number of elements it had.
+    pub fn into_raw_vec(self) -> (RawVec<T, A>, usize) {
+        let (raw, len) = self.into_raw_parts();
+        unsafe {
+            ptr::slice_from_raw_parts_mut(raw.raw().as_ptr(), len)
+                .drop_in_place();
+        }
+        (raw, len)
+    }
+
+    /// Converts the vector into its `RawVec` and the number of elements
+    /// initialized at its start. The elements are not dropped; dropping them
+    /// becomes responsibility of the caller.
+    pub fn into_raw_parts(self) -> (RawVec<T, A>, usize) {
+        let this = mem::ManuallyDrop::new(self);
+        (unsafe { ptr::read(&this.raw) }, this.len)
+    }
+
+    /// The number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Tests if the vector has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements the vector has room for without growing.
+    pub fn capacity(&self) -> usize {
+        self.raw.cap()
+    }
+
+    /// Returns a reference to the allocator backing this vector.
+    pub fn allocator(&self) -> &A {
+        self.raw.allocator()
+    }
+
+    /// The elements of the vector as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.raw.raw().as_ptr(), self.len) }
+    }
+
+    /// The elements of the vector as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.raw.raw().as_ptr(), self.len) }
+    }
+
+    /// Ensures the vector has room for at least `additional` more elements,
+    /// growing it amortizedly if needed. In case of allocation error, the
+    /// handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, the function panics.
+    pub fn reserve(&mut self, additional: usize) {
+        self.raw.reserve(self.len, additional)
+    }
+
+    /// Ensures the vector has room for at least `additional` more elements,
+    /// growing it amortizedly if needed. In case of allocation error or
+    /// overflow calculating the total size, `Err` is returned.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), RawVecErr> {
+        self.raw.try_reserve(self.len, additional)
+    }
+
+    /// Ensures the vector has room for exactly `additional` more elements. In
+    /// case of allocation error, the handler registered via stdlib is called.
+    /// In case of overflow calculating the total size, the function panics.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.raw.reserve_exact(self.len, additional)
+    }
+
+    /// Shrinks the capacity of the vector to its length. In case of
+    /// allocation error, the handler registered via stdlib is called.
+    pub fn shrink_to_fit(&mut self) {
+        self.raw.shrink_to(self.len)
+    }
+
+    /// Appends an element to the back of the vector. In case of allocation
+    /// error, the handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, the function panics.
+    pub fn push(&mut self, val: T) {
+        if self.len == self.raw.cap() {
+            self.reserve(1);
+        }
+        unsafe { self.as_mut_ptr().add(self.len).write(val) };
+        self.len += 1;
+    }
+
+    /// Removes the last element of the vector and returns it, or `None` if the
+    /// vector is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            Some(unsafe { self.as_mut_ptr().add(self.len).read() })
+        }
+    }
+
+    /// Inserts an element at position `index`, shifting all elements after it
+    /// to the right.
+    ///
+    /// # Panics
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, val: T) {
+        let len = self.len;
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+
+        if len == self.raw.cap() {
+            self.reserve(1);
+        }
+
+        unsafe {
+            let ptr = self.as_mut_ptr().add(index);
+            ptr::copy(ptr, ptr.add(1), len - index);
+            ptr.write(val);
+        }
+        self.len += 1;
+    }
+
+    /// Removes and returns the element at position `index`, shifting all
+    /// elements after it to the left.
+    ///
+    /// # Panics
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+
+        unsafe {
+            let ptr = self.as_mut_ptr().add(index);
+            let val = ptr.read();
+            ptr::copy(ptr.add(1), ptr, len - index - 1);
+            self.len -= 1;
+            val
+        }
+    }
+
+    /// Shortens the vector to `len` elements, dropping the rest. If `len` is
+    /// greater than the vector's length, this is a no-op.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            let tail = ptr::slice_from_raw_parts_mut(
+                unsafe { self.as_mut_ptr().add(len) },
+                self.len - len,
+            );
+            self.len = len;
+            unsafe { tail.drop_in_place() };
+        }
+    }
+
+    /// Drops all elements of the vector. The capacity is kept.
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Keeps only the elements for which the passed function returns `true`,
+    /// visiting them in order and keeping their order. If the function panics,
+    /// the elements not yet visited are kept.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let len = self.len;
+        self.len = 0;
+        let mut guard = RetainGuard {
+            vec: self,
+            processed: 0,
+            deleted: 0,
+            len,
+        };
+
+        while guard.processed < len {
+            unsafe {
+                let cur = guard.vec.as_mut_ptr().add(guard.processed);
+                if keep(&*cur) {
+                    if guard.deleted > 0 {
+                        let hole = cur.sub(guard.deleted);
+                        ptr::copy_nonoverlapping(cur, hole, 1);
+                    }
+                    guard.processed += 1;
+                } else {
+                    guard.processed += 1;
+                    guard.deleted += 1;
+                    cur.drop_in_place();
+                }
+            }
+        }
+    }
+
+    /// Removes the elements in the given range and returns an iterator over
+    /// them. The elements after the range are shifted left when the iterator
+    /// is dropped, and the elements not yielded by it are dropped as well.
+    ///
+    /// # Panics
+    /// Panics if the start of the range is greater than its end or if its end
+    /// is greater than the vector's length.
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T, A>
+    where
+        R: RangeBounds<usize>,
+    {
+        let len = self.len;
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1).expect("overflow"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1).expect("overflow"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end,
+            "drain start (is {}) should be <= end (is {})",
+            start,
+            end
+        );
+        assert!(
+            end <= len,
+            "drain end (is {}) should be <= len (is {})",
+            end,
+            len
+        );
+
+        self.len = start;
+        Drain {
+            vec: self,
+            next: start,
+            end,
+            tail: end,
+            tail_len: len - end,
+        }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.raw.raw().as_ptr()
+    }
+}
+
+/// Shifts the kept elements of a vector being retained and fixes its length,
+/// on return or unwind.
+struct RetainGuard<'vec, T, A>
+where
+    A: Allocator,
+{
+    vec: &'vec mut OwnedVec<T, A>,
+    processed: usize,
+    deleted: usize,
+    len: usize,
+}
+
+impl<'vec, T, A> Drop for RetainGuard<'vec, T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        unsafe {
+            if self.deleted > 0 {
+                let src = self.vec.as_mut_ptr().add(self.processed);
+                let dest = src.sub(self.deleted);
+                ptr::copy(src, dest, self.len - self.processed);
+            }
+        }
+        self.vec.len = self.len - self.deleted;
+    }
+}
+
+/// An iterator over the elements removed by `OwnedVec::drain`.
+pub struct Drain<'vec, T, A = Global>
+where
+    A: Allocator,
+{
+    vec: &'vec mut OwnedVec<T, A>,
+    next: usize,
+    end: usize,
+    tail: usize,
+    tail_len: usize,
+}
+
+impl<'vec, T, A> Iterator for Drain<'vec, T, A>
+where
+    A: Allocator,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            None
+        } else {
+            let val = unsafe { self.vec.as_mut_ptr().add(self.next).read() };
+            self.next += 1;
+            Some(val)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<'vec, T, A> DoubleEndedIterator for Drain<'vec, T, A>
+where
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<T> {
+        if self.next == self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some(unsafe { self.vec.as_mut_ptr().add(self.end).read() })
+        }
+    }
+}
+
+impl<'vec, T, A> ExactSizeIterator for Drain<'vec, T, A> where A: Allocator {}
+
+impl<'vec, T, A> FusedIterator for Drain<'vec, T, A> where A: Allocator {}
+
+impl<'vec, T, A> Drop for Drain<'vec, T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        /// Moves the tail back into place, even if dropping the remaining
+        /// elements panics.
+        struct TailGuard<'drain, 'vec, T, A>(&'drain mut Drain<'vec, T, A>)
+        where
+            A: Allocator;
+
+        impl<'drain, 'vec, T, A> Drop for TailGuard<'drain, 'vec, T, A>
+        where
+            A: Allocator,
+        {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                unsafe {
+                    let len = drain.vec.len;
+                    let base = drain.vec.as_mut_ptr();
+                    ptr::copy(
+                        base.add(drain.tail),
+                        base.add(len),
+                        drain.tail_len,
+                    );
+                }
+                drain.vec.len += drain.tail_len;
+            }
+        }
+
+        let remaining = ptr::slice_from_raw_parts_mut(
+            unsafe { self.vec.as_mut_ptr().add(self.next) },
+            self.end - self.next,
+        );
+        self.next = self.end;
+        let _guard = TailGuard(self);
+        unsafe { remaining.drop_in_place() };
+    }
+}
+
+impl<'vec, T, A> fmt::Debug for Drain<'vec, T, A>
+where
+    T: fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        let remaining = unsafe {
+            let start = self.vec.raw.raw().as_ptr().add(self.next);
+            slice::from_raw_parts(start, self.end - self.next)
+        };
+        fmtr.debug_tuple("Drain").field(&remaining).finish()
+    }
+}
+
+impl<T, A> Drop for OwnedVec<T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice() as *mut [T]) }
+    }
+}
+
+impl<T, A> Deref for OwnedVec<T, A>
+where
+    A: Allocator,
+{
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, A> DerefMut for OwnedVec<T, A>
+where
+    A: Allocator,
+{
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, A> fmt::Debug for OwnedVec<T, A>
+where
+    T: fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), fmtr)
+    }
+}
+
+impl<T, A> Clone for OwnedVec<T, A>
+where
+    T: Clone,
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> Self {
+        let mut vec =
+            Self::with_capacity_in(self.len, self.allocator().clone());
+        vec.extend(self.iter().cloned());
+        vec
+    }
+}
+
+impl<T, A> Extend<T> for OwnedVec<T, A>
+where
+    A: Allocator,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for val in iter {
+            self.push(val);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for OwnedVec<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, A> From<RawVec<T, A>> for OwnedVec<T, A>
+where
+    A: Allocator,
+{
+    fn from(raw: RawVec<T, A>) -> Self {
+        Self { raw, len: 0 }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::{super::CountingAlloc, OwnedVec};
+    use alloc::{rc::Rc, vec::Vec};
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn push_pop_insert_remove() {
+        let mut vec = OwnedVec::new();
+        assert_eq!(vec.pop(), None);
+
+        for i in 0 .. 10 {
+            vec.push(i);
+        }
+        assert_eq!(vec.len(), 10);
+        assert!(vec.capacity() >= 10);
+
+        vec.insert(0, 100);
+        vec.insert(11, 200);
+        assert_eq!(vec.remove(5), 4);
+        assert_eq!(vec.pop(), Some(200));
+        assert_eq!(&*vec, &[100, 0, 1, 2, 3, 5, 6, 7, 8, 9]);
+
+        vec.truncate(3);
+        vec.shrink_to_fit();
+        assert_eq!((&*vec, vec.capacity()), (&[100, 0, 1][..], 3));
+    }
+
+    #[test]
+    fn drain() {
+        let mut vec = (0 .. 8).collect::<OwnedVec<_>>();
+        let mut drain = vec.drain(2 ..= 5);
+        assert_eq!(drain.len(), 4);
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.next_back(), Some(5));
+        drop(drain);
+        assert_eq!(&*vec, &[0, 1, 6, 7]);
+
+        assert_eq!(vec.drain(..).collect::<Vec<_>>(), [0, 1, 6, 7]);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn drops_everything() {
+        let counter = Rc::new(());
+        let mut vec = OwnedVec::new();
+        vec.extend((0 .. 10).map(|_| counter.clone()));
+
+        vec.drain(1 .. 4).next();
+        assert_eq!(Rc::strong_count(&counter), 8);
+
+        let mut i = 0;
+        vec.retain(|_| {
+            i += 1;
+            i % 2 == 0
+        });
+        assert_eq!(Rc::strong_count(&counter), 4);
+
+        vec.remove(0);
+        drop(vec.clone());
+        assert_eq!(Rc::strong_count(&counter), 3);
+
+        drop(vec);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn retain_panic_keeps_unvisited() {
+        let mut vec = (0 .. 6).collect::<OwnedVec<_>>();
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            vec.retain(|&elem| {
+                assert!(elem < 3);
+                elem != 1
+            })
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(&*vec, &[0, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn into_raw_vec_drops() {
+        let counter = Rc::new(());
+        let vec = (0 .. 4).map(|_| counter.clone()).collect::<OwnedVec<_>>();
+        let (raw, len) = vec.into_raw_vec();
+        assert_eq!((len, raw.cap()), (4, 4));
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn into_raw_parts() {
+        let counter = CountingAlloc::new();
+        let mut vec = OwnedVec::new_in(&counter);
+        vec.extend([1u32, 2, 3]);
+
+        let (raw, len) = vec.into_raw_parts();
+        assert_eq!(
+            (len, unsafe { &raw.as_slice()[.. len] }),
+            (3, &[1, 2, 3][..])
+        );
+
+        let vec = unsafe { OwnedVec::from_raw_vec(raw, len) };
+        assert_eq!(&*vec, &[1, 2, 3]);
+        drop(vec);
+        assert_eq!(counter.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut vec = OwnedVec::new();
+        for _ in 0 .. 100 {
+            vec.push(());
+        }
+        vec.insert(50, ());
+        vec.retain(|_| true);
+        assert_eq!(vec.drain(10 ..).count(), 91);
+        assert_eq!(vec.len(), 10);
+    }
+}
@@ -0,0 +1,308 @@
+use super::{Allocator, Global, RawVec, RawVecErr};
+use core::{fmt, iter};
+
+const WORD_BITS: usize = usize::BITS as usize;
+
+/// A fixed-length slice allocation whose elements are initialized one by one,
+/// in any order. A bitmap tracks which indices are initialized, so only those
+/// are accessible and dropped on `drop`. This is useful for sparse lookup
+/// tables, where `MaybeUninitAlloc` would only track the whole allocation.
+/// Both the elements and the bitmap are obtained from the allocator `A`, which
+/// is the global allocator by default.
+///
+/// ```rust
+/// use tux_owned_alloc::PartialSlice;
+///
+/// let mut table = PartialSlice::<String>::new(100);
+/// table.set(42, String::from("answer"));
+/// table.set(7, String::from("seven"));
+///
+/// assert_eq!(table.get(42).map(String::as_str), Some("answer"));
+/// assert_eq!(table.get(41), None);
+/// assert_eq!(table.take(7).as_deref(), Some("seven"));
+/// assert_eq!(table.init_count(), 1);
+/// ```
+pub struct PartialSlice<T, A = Global>
+where
+    A: Allocator,
+{
+    slots: RawVec<T, A>,
+    bits: RawVec<usize, A>,
+}
+
+impl<T> PartialSlice<T> {
+    /// Creates room for a slice of `len` elements, none of them initialized.
+    /// In case of allocation error, the handler registered via stdlib is
+    /// called. In case of overflow calculating the total size, the function
+    /// panics.
+    pub fn new(len: usize) -> Self {
+        Self::new_in(len, Global)
+    }
+
+    /// Creates room for a slice of `len` elements, none of them initialized.
+    /// In case of allocation error or overflow calculating the total size,
+    /// `Err` is returned.
+    pub fn try_new(len: usize) -> Result<Self, RawVecErr> {
+        Self::try_new_in(len, Global)
+    }
+}
+
+impl<T, A> PartialSlice<T, A>
+where
+    A: Allocator,
+{
+    /// Creates room for a slice of `len` elements, none of them initialized,
+    /// using the given allocator. In case of allocation error, the handler
+    /// registered via stdlib is called. In case of overflow calculating the
+    /// total size, the function panics.
+    pub fn new_in(len: usize, alloc: A) -> Self
+    where
+        A: Clone,
+    {
+        Self {
+            bits: RawVec::with_capacity_zeroed_in(
+                len.div_ceil(WORD_BITS),
+                alloc.clone(),
+            ),
+            slots: RawVec::with_capacity_in(len, alloc),
+        }
+    }
+
+    /// Creates room for a slice of `len` elements, none of them initialized,
+    /// using the given allocator. In case of allocation error or overflow
+    /// calculating the total size, `Err` is returned.
+    pub fn try_new_in(len: usize, alloc: A) -> Result<Self, RawVecErr>
+    where
+        A: Clone,
+    {
+        Ok(Self {
+            bits: RawVec::try_with_capacity_zeroed_in(
+                len.div_ceil(WORD_BITS),
+                alloc.clone(),
+            )?,
+            slots: RawVec::try_with_capacity_in(len, alloc)?,
+        })
+    }
+
+    /// The number of elements the slice has room for, initialized or not.
+    pub fn len(&self) -> usize {
+        self.slots.cap()
+    }
+
+    /// Tests if the slice has room for no element.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the allocator backing this slice.
+    pub fn allocator(&self) -> &A {
+        self.slots.allocator()
+    }
+
+    /// Tests if the element at position `index` is initialized. Out of bounds
+    /// indices are never initialized.
+    pub fn is_init(&self, index: usize) -> bool {
+        index < self.len() && unsafe { self.bit(index) }
+    }
+
+    /// The number of initialized elements.
+    pub fn init_count(&self) -> usize {
+        let words = unsafe { self.bits.as_slice() };
+        words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Returns a reference to the element at position `index`, or `None` if it
+    /// is not initialized or out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if self.is_init(index) {
+            Some(unsafe { &*self.slots.raw().as_ptr().add(index) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the element at position `index`, or
+    /// `None` if it is not initialized or out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if self.is_init(index) {
+            Some(unsafe { &mut *self.slots.raw().as_ptr().add(index) })
+        } else {
+            None
+        }
+    }
+
+    /// Initializes the element at position `index` to the passed value. If it
+    /// was already initialized, the previous value is returned.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, val: T) -> Option<T> {
+        let len = self.len();
+        assert!(
+            index < len,
+            "index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+
+        let prev = self.take(index);
+        unsafe {
+            self.slots.raw().as_ptr().add(index).write(val);
+            self.set_bit(index, true);
+        }
+        prev
+    }
+
+    /// Moves the element at position `index` out, leaving it uninitialized. If
+    /// it is not initialized or out of bounds, `None` is returned.
+    pub fn take(&mut self, index: usize) -> Option<T> {
+        if self.is_init(index) {
+            unsafe {
+                self.set_bit(index, false);
+                Some(self.slots.raw().as_ptr().add(index).read())
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the initialized elements together with their indices, in
+    /// increasing order of index.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        self.init_indices().map(move |index| {
+            (index, unsafe { &*self.slots.raw().as_ptr().add(index) })
+        })
+    }
+
+    /// Drops all the initialized elements, leaving them uninitialized.
+    pub fn clear(&mut self) {
+        for word_index in 0 .. self.bits.cap() {
+            let word = unsafe { *self.bits.raw().as_ptr().add(word_index) };
+            for index in set_bits(word_index, word) {
+                drop(self.take(index));
+            }
+        }
+    }
+
+    fn init_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let words = unsafe { self.bits.as_slice() };
+        words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, &word)| set_bits(word_index, word))
+    }
+
+    unsafe fn bit(&self, index: usize) -> bool {
+        let word = *self.bits.raw().as_ptr().add(index / WORD_BITS);
+        word & (1 << (index % WORD_BITS)) != 0
+    }
+
+    unsafe fn set_bit(&mut self, index: usize, init: bool) {
+        let word = &mut *self.bits.raw().as_ptr().add(index / WORD_BITS);
+        let mask = 1 << (index % WORD_BITS);
+        if init {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+    }
+}
+
+/// Iterates over the indices of the bits set in the given word of a bitmap.
+fn set_bits(word_index: usize, mut word: usize) -> impl Iterator<Item = usize> {
+    iter::from_fn(move || {
+        if word == 0 {
+            None
+        } else {
+            let bit = word.trailing_zeros() as usize;
+            word &= word - 1;
+            Some(word_index * WORD_BITS + bit)
+        }
+    })
+}
+
+impl<T, A> Drop for PartialSlice<T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, A> fmt::Debug for PartialSlice<T, A>
+where
+    T: fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::{super::CountingAlloc, PartialSlice};
+    use alloc::{format, rc::Rc, vec::Vec};
+    use std::panic;
+
+    #[test]
+    fn set_get_take() {
+        let mut slice = PartialSlice::new(130);
+        assert_eq!((slice.len(), slice.init_count()), (130, 0));
+
+        assert_eq!(slice.set(0, 'a'), None);
+        assert_eq!(slice.set(129, 'b'), None);
+        assert_eq!(slice.set(64, 'c'), None);
+        assert_eq!(slice.set(64, 'd'), Some('c'));
+        *slice.get_mut(0).unwrap() = 'e';
+
+        assert_eq!(slice.iter().collect::<Vec<_>>(), [
+            (0, &'e'),
+            (64, &'d'),
+            (129, &'b')
+        ]);
+        assert_eq!(slice.take(129), Some('b'));
+        assert_eq!(slice.take(129), None);
+        assert_eq!(slice.get(130), None);
+        assert!(!slice.is_init(63) && slice.is_init(64));
+        assert_eq!(format!("{:?}", slice), "{0: 'e', 64: 'd'}");
+    }
+
+    #[test]
+    fn drops_only_initialized() {
+        let counter = Rc::new(());
+        let alloc = CountingAlloc::new();
+        let mut slice = PartialSlice::new_in(200, &alloc);
+
+        for i in (0 .. 200).step_by(3) {
+            slice.set(i, counter.clone());
+        }
+        assert_eq!(Rc::strong_count(&counter), 68);
+
+        slice.set(3, counter.clone());
+        drop(slice.take(6));
+        assert_eq!(Rc::strong_count(&counter), 67);
+
+        drop(slice);
+        assert_eq!(Rc::strong_count(&counter), 1);
+        assert_eq!(alloc.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn set_out_of_bounds_panics() {
+        let res = panic::catch_unwind(|| PartialSlice::new(3).set(3, 0u8));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut slice = PartialSlice::new(1000);
+        slice.set(999, ());
+        assert_eq!(slice.get(999), Some(&()));
+        assert_eq!(slice.take(998), None);
+        assert_eq!(slice.init_count(), 1);
+    }
+}
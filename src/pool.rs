@@ -0,0 +1,187 @@
+use super::{AllocErr, Allocator, OwnedAlloc, RawVec, RawVecErr, UninitAlloc};
+use alloc::vec::Vec;
+use core::{alloc::Layout, cell::RefCell, fmt, mem, ptr::NonNull};
+
+/// Default number of slots in the chunks allocated by a `Pool`.
+const DEFAULT_CHUNK_LEN: usize = 64;
+
+/// An allocation of a `T` whose slot belongs to a `Pool`. Dropping it drops the
+/// value and returns the slot to the pool's free list.
+pub type PoolBox<'pool, T> = OwnedAlloc<T, &'pool Pool<T>>;
+
+/// A typed object pool. Slots for `T`s are carved from large chunks backed by
+/// `RawVec<T>`, and freed slots are kept in a free list to be reused by the
+/// next allocations. The memory of the chunks is only freed when the pool is
+/// dropped. A reference to the pool is an `Allocator`, so the handles it
+/// yields are plain `UninitAlloc` and `OwnedAlloc` (see `PoolBox`) borrowing
+/// the pool.
+///
+/// ```rust
+/// use tux_owned_alloc::Pool;
+///
+/// let pool = Pool::new();
+/// let first = pool.alloc(1u64);
+/// let raw = first.raw();
+/// drop(first);
+///
+/// let second = pool.alloc(2u64);
+/// assert_eq!(second.raw(), raw);
+/// assert_eq!(*second, 2);
+/// ```
+pub struct Pool<T> {
+    chunks: RefCell<Vec<RawVec<T>>>,
+    free: RefCell<Vec<NonNull<T>>>,
+    chunk_len: usize,
+}
+
+impl<T> Pool<T> {
+    /// Creates a new pool with the default chunk length. No allocation is
+    /// performed until the first allocation request.
+    pub fn new() -> Self {
+        Self::with_chunk_len(DEFAULT_CHUNK_LEN)
+    }
+
+    /// Creates a new pool whose chunks hold the given number of slots. A
+    /// length of `0` is treated as `1`. No allocation is performed until the
+    /// first allocation request.
+    pub fn with_chunk_len(chunk_len: usize) -> Self {
+        Self {
+            chunks: RefCell::new(Vec::new()),
+            free: RefCell::new(Vec::new()),
+            chunk_len: chunk_len.max(1),
+        }
+    }
+
+    /// Takes a slot from the pool and initializes it to the passed argument. In
+    /// case of allocation error, the handler registered via stdlib is called.
+    pub fn alloc(&self, val: T) -> PoolBox<'_, T> {
+        OwnedAlloc::new_in(val, self)
+    }
+
+    /// Takes a slot from the pool and initializes it to the passed argument. In
+    /// case of allocation error, `Err` is returned.
+    pub fn try_alloc(&self, val: T) -> Result<PoolBox<'_, T>, AllocErr> {
+        OwnedAlloc::try_new_in(val, self)
+    }
+
+    /// Takes an uninitialized slot from the pool. In case of allocation error,
+    /// the handler registered via stdlib is called.
+    pub fn uninit(&self) -> UninitAlloc<T, &Self> {
+        UninitAlloc::new_in(self)
+    }
+
+    /// Takes an uninitialized slot from the pool. In case of allocation error,
+    /// `Err` is returned.
+    pub fn try_uninit(&self) -> Result<UninitAlloc<T, &Self>, AllocErr> {
+        UninitAlloc::try_new_in(self)
+    }
+
+    /// The total number of slots owned by the pool, both free and in use.
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(RawVec::cap).sum()
+    }
+
+    /// The number of slots currently in the free list.
+    pub fn free_slots(&self) -> usize {
+        self.free.borrow().len()
+    }
+
+    fn grow(&self, layout: Layout) -> Result<(), AllocErr> {
+        let chunk = RawVec::<T>::try_with_capacity(self.chunk_len).map_err(
+            |err| match err {
+                RawVecErr::Alloc(err) => err,
+                RawVecErr::Layout(_) => AllocErr { layout },
+            },
+        )?;
+
+        let mut free = self.free.borrow_mut();
+        free.reserve(chunk.cap());
+        for i in (0 .. chunk.cap()).rev() {
+            let slot = unsafe { chunk.raw().as_ptr().add(i) };
+            free.push(unsafe { NonNull::new_unchecked(slot) });
+        }
+        self.chunks.borrow_mut().push(chunk);
+        Ok(())
+    }
+}
+
+unsafe impl<T> Allocator for Pool<T> {
+    fn alloc(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        if layout.size() > mem::size_of::<T>()
+            || layout.align() > mem::align_of::<T>()
+        {
+            return Err(AllocErr { layout });
+        }
+
+        if self.free.borrow().is_empty() {
+            self.grow(layout)?;
+        }
+
+        let slot = self.free.borrow_mut().pop().ok_or(AllocErr { layout })?;
+        Ok(slot.cast())
+    }
+
+    unsafe fn dealloc(&self, ptr: NonNull<u8>, _layout: Layout) {
+        self.free.borrow_mut().push(ptr.cast());
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmtr,
+            "Pool {{ capacity: {}, free: {} }}",
+            self.capacity(),
+            self.free_slots()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::String, vec::Vec};
+    use super::Pool;
+
+    #[test]
+    fn slots_are_reused() {
+        let pool = Pool::with_chunk_len(4);
+        let first = pool.alloc(String::from("first"));
+        let raw = first.raw();
+        drop(first);
+
+        let second = pool.alloc(String::from("second"));
+        assert_eq!(second.raw(), raw);
+        assert_eq!(*second, "second");
+        assert_eq!(pool.capacity(), 4);
+        assert_eq!(pool.free_slots(), 3);
+    }
+
+    #[test]
+    fn grows_by_chunks() {
+        let pool = Pool::with_chunk_len(2);
+        let allocs = (0 .. 5).map(|i| pool.alloc(i)).collect::<Vec<_>>();
+
+        assert_eq!(pool.capacity(), 6);
+        assert_eq!(pool.free_slots(), 1);
+        for (i, alloc) in allocs.iter().enumerate() {
+            assert_eq!(**alloc, i);
+        }
+
+        drop(allocs);
+        assert_eq!(pool.free_slots(), 6);
+    }
+
+    #[test]
+    fn move_out_and_back() {
+        let pool = Pool::new();
+        let (val, uninit) = pool.alloc(10u32).move_inner();
+        assert_eq!(val, 10);
+        assert_eq!(*uninit.init(val + 1), 11);
+    }
+}
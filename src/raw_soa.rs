@@ -0,0 +1,471 @@
+use super::{
+    raw_vec::handle_raw_vec_err,
+    Allocator,
+    Global,
+    GrowthPolicy,
+    LayoutErr,
+    RawVecErr,
+};
+use core::{
+    alloc::Layout,
+    fmt,
+    marker::PhantomData,
+    ptr::{self, NonNull},
+    slice,
+};
+
+/// Tuples of column types which a `RawSoa` can store, one array per tuple
+/// field. It is implemented for tuples of up to 6 fields, and there should be
+/// no need to implement it for other types.
+///
+/// # Safety
+/// Implementors must compute layouts and column pointers which are valid for
+/// each other: the pointers returned by `columns` must be properly aligned and
+/// must not overlap for the layout returned by `layout`.
+pub unsafe trait SoaColumns: Sized {
+    /// A tuple of pointers to the first element of each column.
+    type Ptrs: Copy;
+    /// A tuple of shared slices of each column.
+    type Slices<'soa>
+    where
+        Self: 'soa;
+    /// A tuple of mutable slices of each column.
+    type SlicesMut<'soa>
+    where
+        Self: 'soa;
+
+    /// Computes the layout of a block holding `cap` elements of each column.
+    /// In case of overflow calculating the total size, `Err` is returned.
+    fn layout(cap: usize) -> Result<Layout, LayoutErr>;
+
+    /// Dangling, well-aligned pointers for each column, used when the block is
+    /// zero-sized.
+    fn dangling() -> Self::Ptrs;
+
+    /// Computes the pointers to each column of a block holding `cap` elements
+    /// of each column and starting at `base`.
+    ///
+    /// # Safety
+    /// `base` must be a block allocated with `layout(cap)`, which must not
+    /// have failed.
+    unsafe fn columns(base: NonNull<u8>, cap: usize) -> Self::Ptrs;
+
+    /// Copies the first `count` elements of each column from `src` to `dest`.
+    ///
+    /// # Safety
+    /// Both sides must be valid for `count` elements of each column and must
+    /// not overlap.
+    unsafe fn copy(src: Self::Ptrs, dest: Self::Ptrs, count: usize);
+
+    /// Makes slices of `len` elements of each column.
+    ///
+    /// # Safety
+    /// The pointers must be valid for `len` initialized elements of each
+    /// column for the lifetime `'soa`.
+    unsafe fn slices<'soa>(ptrs: Self::Ptrs, len: usize) -> Self::Slices<'soa>;
+
+    /// Makes mutable slices of `len` elements of each column.
+    ///
+    /// # Safety
+    /// The pointers must be valid for `len` initialized elements of each
+    /// column for the lifetime `'soa`, and must not be aliased.
+    unsafe fn slices_mut<'soa>(
+        ptrs: Self::Ptrs,
+        len: usize,
+    ) -> Self::SlicesMut<'soa>;
+}
+
+macro_rules! impl_soa_columns {
+    ($($ty:ident $idx:tt),*) => {
+        unsafe impl<$($ty),*> SoaColumns for ($($ty,)*) {
+            type Ptrs = ($(NonNull<$ty>,)*);
+            type Slices<'soa> = ($(&'soa [$ty],)*) where Self: 'soa;
+            type SlicesMut<'soa> = ($(&'soa mut [$ty],)*) where Self: 'soa;
+
+            fn layout(cap: usize) -> Result<Layout, LayoutErr> {
+                let layout = Layout::new::<()>();
+                $(
+                    let array = Layout::array::<$ty>(cap)?;
+                    let (layout, _) = layout.extend(array)?;
+                )*
+                Ok(layout.pad_to_align())
+            }
+
+            fn dangling() -> Self::Ptrs {
+                ($(NonNull::<$ty>::dangling(),)*)
+            }
+
+            #[allow(unused_assignments)]
+            unsafe fn columns(base: NonNull<u8>, cap: usize) -> Self::Ptrs {
+                let mut layout = Layout::new::<()>();
+                ($({
+                    let array = Layout::array::<$ty>(cap).unwrap_unchecked();
+                    let (next, offset) =
+                        layout.extend(array).unwrap_unchecked();
+                    layout = next;
+                    NonNull::new_unchecked(base.as_ptr().add(offset)).cast()
+                },)*)
+            }
+
+            unsafe fn copy(src: Self::Ptrs, dest: Self::Ptrs, count: usize) {
+                $(ptr::copy_nonoverlapping(
+                    src.$idx.as_ptr(),
+                    dest.$idx.as_ptr(),
+                    count,
+                );)*
+            }
+
+            unsafe fn slices<'soa>(
+                ptrs: Self::Ptrs,
+                len: usize,
+            ) -> Self::Slices<'soa> {
+                ($(slice::from_raw_parts(ptrs.$idx.as_ptr(), len),)*)
+            }
+
+            unsafe fn slices_mut<'soa>(
+                ptrs: Self::Ptrs,
+                len: usize,
+            ) -> Self::SlicesMut<'soa> {
+                ($(slice::from_raw_parts_mut(ptrs.$idx.as_ptr(), len),)*)
+            }
+        }
+    };
+}
+
+impl_soa_columns!(A 0);
+impl_soa_columns!(A 0, B 1);
+impl_soa_columns!(A 0, B 1, C 2);
+impl_soa_columns!(A 0, B 1, C 2, D 3);
+impl_soa_columns!(A 0, B 1, C 2, D 3, E 4);
+impl_soa_columns!(A 0, B 1, C 2, D 3, E 4, F 5);
+
+/// Raw struct-of-arrays allocation. Like a `RawVec` per column, but all the
+/// columns share the same capacity and live in a single block of memory, so
+/// growing them needs a single allocation. The columns are given as a tuple,
+/// e.g. `RawSoa<(f32, f32, u8)>`. No initialization or deinitialization of the
+/// elements is performed. Resizing allocates a new block and copies each
+/// column, since the offsets of the columns depend on the capacity. If the
+/// size of the block is zero, no allocation is performed and dangling pointers
+/// are used. The memory is obtained from the allocator `A`, which is the
+/// global allocator by default.
+///
+/// ```rust
+/// use tux_owned_alloc::RawSoa;
+///
+/// let mut soa = RawSoa::<(u64, u8)>::with_capacity(2);
+/// let (ids, flags) = soa.ptrs();
+/// unsafe {
+///     ids.as_ptr().write(10);
+///     flags.as_ptr().write(1);
+/// }
+///
+/// soa.resize(100);
+/// let (ids, flags) = unsafe { soa.slices() };
+/// assert_eq!((ids.len(), ids[0], flags[0]), (100, 10, 1));
+/// ```
+pub struct RawSoa<C, A = Global>
+where
+    C: SoaColumns,
+    A: Allocator,
+{
+    base: NonNull<u8>,
+    ptrs: C::Ptrs,
+    cap: usize,
+    alloc: A,
+    _marker: PhantomData<C>,
+}
+
+impl<C> RawSoa<C>
+where
+    C: SoaColumns,
+{
+    /// Creates a new `RawSoa` of capacity `0` and dangling pointers. No
+    /// allocation is performed.
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Creates a new `RawSoa` with a given capacity for every column. In case
+    /// of allocation error, the handler registered via stdlib is called. In
+    /// case of overflow calculating the total size, the function panics.
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    /// Creates a new `RawSoa` with a given capacity for every column. In case
+    /// of allocation error or overflow calculating the total size, `Err` is
+    /// returned.
+    pub fn try_with_capacity(cap: usize) -> Result<Self, RawVecErr> {
+        Self::try_with_capacity_in(cap, Global)
+    }
+}
+
+impl<C> Default for RawSoa<C>
+where
+    C: SoaColumns,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C, A> RawSoa<C, A>
+where
+    C: SoaColumns,
+    A: Allocator,
+{
+    /// Creates a new `RawSoa` of capacity `0` and dangling pointers, using the
+    /// given allocator. No allocation is performed.
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            base: NonNull::dangling(),
+            ptrs: C::dangling(),
+            cap: 0,
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a new `RawSoa` with a given capacity for every column using the
+    /// given allocator. In case of allocation error, the handler registered
+    /// via stdlib is called. In case of overflow calculating the total size,
+    /// the function panics.
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        let mut this = Self::new_in(alloc);
+        this.resize(cap);
+        this
+    }
+
+    /// Creates a new `RawSoa` with a given capacity for every column using the
+    /// given allocator. In case of allocation error or overflow calculating
+    /// the total size, `Err` is returned.
+    pub fn try_with_capacity_in(
+        cap: usize,
+        alloc: A,
+    ) -> Result<Self, RawVecErr> {
+        let mut this = Self::new_in(alloc);
+        this.try_resize(cap)?;
+        Ok(this)
+    }
+
+    /// The requested capacity of every column.
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    /// The raw non-null pointers to the first element of each column.
+    pub fn ptrs(&self) -> C::Ptrs {
+        self.ptrs
+    }
+
+    /// Returns a reference to the allocator backing this `RawSoa`.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Encodes the columns as shared slices with length equal to the capacity.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because if the index of an uninitialized
+    /// element is accessed incorrectly, undefined behavior occurs.
+    pub unsafe fn slices(&self) -> C::Slices<'_> {
+        C::slices(self.ptrs, self.cap)
+    }
+
+    /// Encodes the columns as mutable slices with length equal to the
+    /// capacity.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because if the index of an uninitialized
+    /// element is accessed incorrectly, undefined behavior occurs.
+    pub unsafe fn slices_mut(&mut self) -> C::SlicesMut<'_> {
+        C::slices_mut(self.ptrs, self.cap)
+    }
+
+    /// Resizes every column to the given capacity. In case of allocation
+    /// error, the handler registered via stdlib is called. In case of overflow
+    /// calculating the total size, the function panics.
+    pub fn resize(&mut self, new_cap: usize) {
+        handle_raw_vec_err(self.try_resize(new_cap))
+    }
+
+    /// Resizes every column to the given capacity, keeping the first elements
+    /// of each column which fit. In case of allocation error or overflow
+    /// calculating the total size, `Err` is returned and the original
+    /// allocation is untouched.
+    pub fn try_resize(&mut self, new_cap: usize) -> Result<(), RawVecErr> {
+        let layout = C::layout(new_cap)?;
+
+        let (base, ptrs) = if layout.size() == 0 {
+            (NonNull::dangling(), C::dangling())
+        } else {
+            let base = self.alloc.alloc(layout)?;
+            (base, unsafe { C::columns(base, new_cap) })
+        };
+
+        unsafe { C::copy(self.ptrs, ptrs, self.cap.min(new_cap)) };
+        self.free();
+        self.base = base;
+        self.ptrs = ptrs;
+        self.cap = new_cap;
+        Ok(())
+    }
+
+    /// Ensures every column has room for at least `additional` elements after
+    /// the first `used` ones, doubling the capacity if it has to grow. In case
+    /// of allocation error, the handler registered via stdlib is called. In
+    /// case of overflow calculating the total size, the function panics.
+    pub fn reserve(&mut self, used: usize, additional: usize) {
+        handle_raw_vec_err(self.try_reserve(used, additional))
+    }
+
+    /// Ensures every column has room for at least `additional` elements after
+    /// the first `used` ones, doubling the capacity if it has to grow. In case
+    /// of allocation error or overflow calculating the total size, `Err` is
+    /// returned and the original allocation is untouched.
+    pub fn try_reserve(
+        &mut self,
+        used: usize,
+        additional: usize,
+    ) -> Result<(), RawVecErr> {
+        let required = used.checked_add(additional).ok_or(LayoutErr)?;
+        if required <= self.cap {
+            return Ok(());
+        }
+
+        let amortized = GrowthPolicy::Double.next_cap(self.cap).max(required);
+        if C::layout(amortized).is_ok() {
+            self.try_resize(amortized)
+        } else {
+            self.try_resize(required)
+        }
+    }
+
+    fn free(&self) {
+        if let Ok(layout) = C::layout(self.cap) {
+            if layout.size() != 0 {
+                unsafe { self.alloc.dealloc(self.base, layout) }
+            }
+        }
+    }
+}
+
+impl<C, A> fmt::Debug for RawSoa<C, A>
+where
+    C: SoaColumns,
+    A: Allocator,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "RawSoa {{ pointer {:?}, cap: {} }}", self.base, self.cap)
+    }
+}
+
+impl<C, A> Drop for RawSoa<C, A>
+where
+    C: SoaColumns,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.free();
+    }
+}
+
+unsafe impl<C, A> Send for RawSoa<C, A>
+where
+    C: SoaColumns + Send,
+    A: Allocator + Send,
+{
+}
+
+unsafe impl<C, A> Sync for RawSoa<C, A>
+where
+    C: SoaColumns + Sync,
+    A: Allocator + Sync,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        super::{CountingAlloc, FailPolicy, FailingAlloc, RawVecErr},
+        RawSoa,
+        SoaColumns,
+    };
+    use core::{alloc::Layout, mem};
+
+    #[test]
+    fn single_block() {
+        let counter = CountingAlloc::new();
+        let mut soa = RawSoa::<(u8, u64, u16), _>::new_in(&counter);
+
+        for cap in [5, 40, 3] {
+            soa.resize(cap);
+            let (bytes, words, halves) = soa.ptrs();
+            let bytes = bytes.as_ptr() as usize;
+            let words = words.as_ptr() as usize;
+            let halves = halves.as_ptr() as usize;
+
+            assert_eq!(words % mem::align_of::<u64>(), 0);
+            assert_eq!(halves % mem::align_of::<u16>(), 0);
+            assert!(bytes + cap <= words);
+            assert!(words + cap * 8 <= halves);
+            assert_eq!(
+                counter.stats().live_bytes,
+                <(u8, u64, u16)>::layout(cap).unwrap().size()
+            );
+        }
+
+        assert_eq!(counter.stats().allocs, 3);
+        drop(soa);
+        assert_eq!(counter.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn resize_keeps_columns() {
+        let mut soa = RawSoa::<(u32, u8)>::with_capacity(3);
+        unsafe {
+            let (nums, flags) = soa.slices_mut();
+            nums.copy_from_slice(&[1, 2, 3]);
+            flags.copy_from_slice(&[4, 5, 6]);
+        }
+
+        soa.reserve(3, 1);
+        assert_eq!(soa.cap(), 6);
+        let (nums, flags) = unsafe { soa.slices() };
+        assert_eq!(&nums[.. 3], &[1, 2, 3]);
+        assert_eq!(&flags[.. 3], &[4, 5, 6]);
+
+        soa.resize(2);
+        let (nums, flags) = unsafe { soa.slices() };
+        assert_eq!((nums, flags), (&[1, 2][..], &[4, 5][..]));
+    }
+
+    #[test]
+    fn zero_sized() {
+        let counter = CountingAlloc::new();
+        let mut soa =
+            RawSoa::<((), [u8; 0]), _>::with_capacity_in(10, &counter);
+        soa.resize(usize::MAX);
+        assert_eq!(soa.cap(), usize::MAX);
+        soa.resize(0);
+        drop(soa);
+        assert_eq!(counter.stats().allocs, 0);
+
+        let layout = <((), u16)>::layout(4).unwrap();
+        assert_eq!(layout, Layout::new::<[u16; 4]>());
+    }
+
+    #[test]
+    fn failures_leave_allocation_untouched() {
+        let failing = FailingAlloc::new(FailPolicy::AboveBytes(64));
+        let mut soa = RawSoa::<(u32, u32), _>::with_capacity_in(8, &failing);
+        let ptrs = soa.ptrs();
+
+        assert!(matches!(soa.try_resize(9), Err(RawVecErr::Alloc(_))));
+        assert!(matches!(
+            soa.try_resize(usize::MAX),
+            Err(RawVecErr::Layout(_))
+        ));
+        assert_eq!((soa.cap(), soa.ptrs()), (8, ptrs));
+    }
+}
@@ -1,10 +1,11 @@
-use super::{AllocErr, LayoutErr, RawVecErr, UninitAlloc};
-use std::{
-    alloc::{alloc, dealloc, handle_alloc_error, realloc, Layout},
+use super::{Aligned, Allocator, Global, LayoutErr, RawVecErr, UninitAlloc};
+use alloc::{alloc::handle_alloc_error, vec::Vec};
+use core::{
+    alloc::Layout,
     fmt,
     marker::PhantomData,
-    mem,
-    ptr::NonNull,
+    mem::{self, ManuallyDrop},
+    ptr::{self, NonNull},
     slice,
 };
 
@@ -14,12 +15,12 @@ use std::{
 /// the elements is performed. This type may be useful for `Vec`-like types. If
 /// the size of the allocation is zero, no allocation is performed and a
 /// dangling pointer is used (just like in `std`). For the drop checker, the
-/// type acts as if it contains a `T` due to usage of `PhantomData<T>`.
+/// type acts as if it contains a `T` due to usage of `PhantomData<T>`. The
+/// memory is obtained from the allocator `A`, which is the global allocator by
+/// default.
 ///
 /// ```rust
-/// extern crate owned_alloc;
-///
-/// use owned_alloc::RawVec;
+/// use tux_owned_alloc::RawVec;
 ///
 /// let mut vec = RawVec::<usize>::with_capacity(200);
 /// assert_eq!(200, vec.cap());
@@ -29,12 +30,42 @@ use std::{
 /// assert_eq!(354, vec.cap());
 /// assert_eq!(354, unsafe { vec.as_slice().len() });
 /// ```
-pub struct RawVec<T> {
+pub struct RawVec<T, A = Global>
+where
+    A: Allocator,
+{
     nnptr: NonNull<T>,
     cap: usize,
+    alloc: A,
     _marker: PhantomData<T>,
 }
 
+/// How the capacity of a `RawVec` grows when `grow_amortized` needs more room.
+/// The policy only gives a lower bound: the new capacity is never smaller than
+/// the one required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GrowthPolicy {
+    /// Doubles the capacity. This is what `reserve` uses.
+    #[default]
+    Double,
+    /// Grows the capacity by half of itself.
+    OneAndHalf,
+    /// Grows the capacity by the given number of elements.
+    FixedStep(usize),
+}
+
+impl GrowthPolicy {
+    /// The capacity this policy grows the given capacity to, saturating at
+    /// `usize::MAX`.
+    pub fn next_cap(self, cap: usize) -> usize {
+        match self {
+            GrowthPolicy::Double => cap.saturating_mul(2),
+            GrowthPolicy::OneAndHalf => cap.saturating_add(cap / 2),
+            GrowthPolicy::FixedStep(step) => cap.saturating_add(step),
+        }
+    }
+}
+
 impl<T> Default for RawVec<T> {
     fn default() -> Self {
         Self::new()
@@ -45,36 +76,35 @@ impl<T> RawVec<T> {
     /// Creates a new `RawVec` of capacity `0` and a dangling pointer. No
     /// allocation is performed.
     pub fn new() -> Self {
-        Self { nnptr: NonNull::dangling(), cap: 0, _marker: PhantomData }
+        Self::new_in(Global)
     }
 
-
     /// Creates a new `RawVec` with a given capacity. In case of allocation
     /// error, the handler registered via stdlib is called. In case of overflow
     /// calculating the total size, the function panics.
     pub fn with_capacity(cap: usize) -> Self {
-        match Self::try_with_capacity(cap) {
-            Ok(this) => this,
-            Err(RawVecErr::Alloc(err)) => handle_alloc_error(err.layout),
-            Err(RawVecErr::Layout(err)) => {
-                panic!("Capacity overflows memory size: {}", err)
-            }
-        }
+        Self::with_capacity_in(cap, Global)
     }
 
     /// Creates a new `RawVec` with a given capacity. In case of allocation
     /// error or overflow calculating the total size, `Err` is returned.
     pub fn try_with_capacity(cap: usize) -> Result<Self, RawVecErr> {
-        let layout = Self::make_layout(cap)?;
-        let res = if layout.size() == 0 {
-            Ok(NonNull::dangling())
-        } else {
-            NonNull::new(unsafe { alloc(layout) })
-                .map(NonNull::cast::<T>)
-                .ok_or_else(|| AllocErr { layout }.into())
-        };
+        Self::try_with_capacity_in(cap, Global)
+    }
+
+    /// Creates a new `RawVec` with a given capacity whose bytes are all zero.
+    /// In case of allocation error, the handler registered via stdlib is
+    /// called. In case of overflow calculating the total size, the function
+    /// panics.
+    pub fn with_capacity_zeroed(cap: usize) -> Self {
+        Self::with_capacity_zeroed_in(cap, Global)
+    }
 
-        res.map(|nnptr| Self { nnptr, cap, _marker: PhantomData })
+    /// Creates a new `RawVec` with a given capacity whose bytes are all zero.
+    /// In case of allocation error or overflow calculating the total size,
+    /// `Err` is returned.
+    pub fn try_with_capacity_zeroed(cap: usize) -> Result<Self, RawVecErr> {
+        Self::try_with_capacity_zeroed_in(cap, Global)
     }
 
     /// Creates a `RawVec` from a plain old standard library `Vec`. Beware, only
@@ -88,11 +118,10 @@ impl<T> RawVec<T> {
     /// `RawVec` allocate in the same way. They probably do in the Rust version
     /// you are using, but there are no future guarantees.
     pub unsafe fn from_vec(mut vec: Vec<T>) -> Self {
-        let this = Self {
-            nnptr: NonNull::new_unchecked(vec.as_mut_ptr()),
-            cap: vec.capacity(),
-            _marker: PhantomData,
-        };
+        let this = Self::from_raw_parts(
+            NonNull::new_unchecked(vec.as_mut_ptr()),
+            vec.capacity(),
+        );
         mem::forget(vec);
         this
     }
@@ -104,7 +133,7 @@ impl<T> RawVec<T> {
     /// undefined behaviour. Passing wrong capacity also leads to undefined
     /// behaviour.
     pub unsafe fn from_raw_parts(nnptr: NonNull<T>, cap: usize) -> Self {
-        Self { nnptr, cap, _marker: PhantomData }
+        Self::from_raw_parts_in(nnptr, cap, Global)
     }
 
     /// Recreate the `RawVec` from a raw non-null pointer to a slice with length
@@ -113,18 +142,156 @@ impl<T> RawVec<T> {
     /// # Safety
     /// This functions is `unsafe` because passing the wrong pointer leads to
     /// undefined behaviour, including passing a pointer with the wrong length.
-    pub unsafe fn from_raw_slice(mut raw: NonNull<[T]>) -> Self {
+    pub unsafe fn from_raw_slice(raw: NonNull<[T]>) -> Self {
+        Self::from_raw_slice_in(raw, Global)
+    }
+
+    /// Creates a plain old standard library `Vec` from the `RawVec` and a given
+    /// length.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because there are no guarantees that `Vec` and
+    /// `RawVec` allocate in the same way. They probably do in the Rust version
+    /// you are using, but there are no future guarantees. Also, the length
+    /// argument must be passed correctly, since the elements until the given
+    /// length will be considered correctly, but the `RawVec` initialize no
+    /// element.
+    pub unsafe fn into_vec(self, len: usize) -> Vec<T> {
+        let vec = Vec::from_raw_parts(self.nnptr.as_ptr(), len, self.cap);
+        mem::forget(self);
+        vec
+    }
+}
+
+impl<T> RawVec<T, Aligned> {
+    /// Creates a new `RawVec` with a given capacity whose allocation is aligned
+    /// to at least `align`. The alignment is kept by resizing. In case of
+    /// allocation error, the handler registered via stdlib is called. In case
+    /// of overflow calculating the total size or invalid alignment, the
+    /// function panics.
+    pub fn with_capacity_aligned(cap: usize, align: usize) -> Self {
+        Self::with_capacity_in(cap, Aligned::new(align))
+    }
+
+    /// Creates a new `RawVec` with a given capacity whose allocation is aligned
+    /// to at least `align`. The alignment is kept by resizing. In case of
+    /// allocation error, overflow calculating the total size or invalid
+    /// alignment, `Err` is returned.
+    pub fn try_with_capacity_aligned(
+        cap: usize,
+        align: usize,
+    ) -> Result<Self, RawVecErr> {
+        Self::try_with_capacity_in(cap, Aligned::try_new(align)?)
+    }
+}
+
+impl<T, A> RawVec<T, A>
+where
+    A: Allocator,
+{
+    /// Creates a new `RawVec` of capacity `0` and a dangling pointer, using the
+    /// given allocator. No allocation is performed.
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            nnptr: NonNull::dangling(),
+            cap: 0,
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a new `RawVec` with a given capacity using the given allocator.
+    /// In case of allocation error, the handler registered via stdlib is
+    /// called. In case of overflow calculating the total size, the function
+    /// panics.
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        handle_raw_vec_err(Self::try_with_capacity_in(cap, alloc))
+    }
+
+    /// Creates a new `RawVec` with a given capacity using the given allocator.
+    /// In case of allocation error or overflow calculating the total size,
+    /// `Err` is returned.
+    pub fn try_with_capacity_in(
+        cap: usize,
+        alloc: A,
+    ) -> Result<Self, RawVecErr> {
+        Self::try_with_capacity_with(cap, alloc, false)
+    }
+
+    /// Creates a new `RawVec` with a given capacity whose bytes are all zero,
+    /// using the given allocator. In case of allocation error, the handler
+    /// registered via stdlib is called. In case of overflow calculating the
+    /// total size, the function panics.
+    pub fn with_capacity_zeroed_in(cap: usize, alloc: A) -> Self {
+        handle_raw_vec_err(Self::try_with_capacity_zeroed_in(cap, alloc))
+    }
+
+    /// Creates a new `RawVec` with a given capacity whose bytes are all zero,
+    /// using the given allocator. In case of allocation error or overflow
+    /// calculating the total size, `Err` is returned.
+    pub fn try_with_capacity_zeroed_in(
+        cap: usize,
+        alloc: A,
+    ) -> Result<Self, RawVecErr> {
+        Self::try_with_capacity_with(cap, alloc, true)
+    }
+
+    fn try_with_capacity_with(
+        cap: usize,
+        alloc: A,
+        zeroed: bool,
+    ) -> Result<Self, RawVecErr> {
+        let layout = Self::make_layout(cap)?;
+        let res = if layout.size() == 0 {
+            Ok(NonNull::dangling())
+        } else if zeroed {
+            alloc.alloc_zeroed(layout).map(NonNull::cast::<T>)
+        } else {
+            alloc.alloc(layout).map(NonNull::cast::<T>)
+        };
+
+        res.map(|nnptr| Self { nnptr, cap, alloc, _marker: PhantomData })
+            .map_err(Into::into)
+    }
+
+    /// Recreate the `RawVec` from a raw non-null pointer, a capacity and the
+    /// allocator which allocated it.
+    ///
+    /// # Safety
+    /// This functions is `unsafe` because passing the wrong pointer leads to
+    /// undefined behaviour. Passing wrong capacity or an allocator other than
+    /// the one used to allocate the pointer also leads to undefined behaviour.
+    pub unsafe fn from_raw_parts_in(
+        nnptr: NonNull<T>,
+        cap: usize,
+        alloc: A,
+    ) -> Self {
+        Self { nnptr, cap, alloc, _marker: PhantomData }
+    }
+
+    /// Recreate the `RawVec` from a raw non-null pointer to a slice with length
+    /// equal to the `RawVec`'s capacity and the allocator which allocated it.
+    ///
+    /// # Safety
+    /// This functions is `unsafe` because passing the wrong pointer leads to
+    /// undefined behaviour, including passing a pointer with the wrong length.
+    /// Passing an allocator other than the one used to allocate the pointer
+    /// also leads to undefined behaviour.
+    pub unsafe fn from_raw_slice_in(mut raw: NonNull<[T]>, alloc: A) -> Self {
         Self {
             nnptr: NonNull::new_unchecked(raw.as_mut().as_mut_ptr()),
             cap: raw.as_ref().len(),
+            alloc,
             _marker: PhantomData,
         }
     }
 
     /// The requested allocation capacity. It is guaranteed to be the capacity
     /// passed to the last capacity-modifier method. Those are
-    /// `with_capacity`, `try_with_capacity` and `resize`. The methods `new`
-    /// and `try_new` initialize the capacity to `0`.
+    /// `with_capacity`, `try_with_capacity` and `resize`, or computed by the
+    /// methods `reserve`, `grow_amortized` and `shrink_to` (and their
+    /// variants). The methods `new` and `try_new` initialize the capacity to
+    /// `0`.
     pub fn cap(&self) -> usize {
         self.cap
     }
@@ -134,6 +301,11 @@ impl<T> RawVec<T> {
         self.nnptr
     }
 
+    /// Returns a reference to the allocator backing this `RawVec`.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     /// The raw non-null pointer to the slice with length equal to the
     /// `RawVec`'s capacity.
     pub fn raw_slice(&self) -> NonNull<[T]> {
@@ -141,13 +313,22 @@ impl<T> RawVec<T> {
     }
 
     /// "Forgets" dropping the allocation and returns a raw non-null pointer to
-    /// the slice with length equal to the `RawVec`'s capacity.
+    /// the slice with length equal to the `RawVec`'s capacity. The allocator is
+    /// forgotten as well.
     pub fn into_raw_slice(self) -> NonNull<[T]> {
         let ptr = self.raw_slice();
         mem::forget(self);
         ptr
     }
 
+    /// "Forgets" dropping the allocation and returns a raw non-null pointer to
+    /// the slice with length equal to the `RawVec`'s capacity, together with
+    /// the allocator which allocated it.
+    pub fn into_raw_slice_with_alloc(self) -> (NonNull<[T]>, A) {
+        let this = ManuallyDrop::new(self);
+        (this.raw_slice(), unsafe { ptr::read(&this.alloc) })
+    }
+
     /// Encodes the `RawVec` as an immutable reference to a slice with length
     /// equal to the capacity.
     ///
@@ -168,65 +349,163 @@ impl<T> RawVec<T> {
         slice::from_raw_parts_mut(self.nnptr.as_ptr(), self.cap())
     }
 
-    /// Creates a plain old standard library `Vec` from the `RawVec` and a given
-    /// length.
-    ///
-    /// # Safety
-    /// This function is `unsafe` because there are no guarantees that `Vec` and
-    /// `RawVec` allocate in the same way. They probably do in the Rust version
-    /// you are using, but there are no future guarantees. Also, the length
-    /// argument must be passed correctly, since the elements until the given
-    /// length will be considered correctly, but the `RawVec` initialize no
-    /// element.
-    pub unsafe fn into_vec(self, len: usize) -> Vec<T> {
-        let vec = Vec::from_raw_parts(self.nnptr.as_ptr(), len, self.cap);
-        mem::forget(self);
-        vec
-    }
-
     /// Resizes the `RawVec` with a given capacity. In case of allocation
     /// error, the handler registered via stdlib is called. In case of overflow
-    /// calculating the total size, the function panics.
+    /// calculating the total size, the function panics. Memory added by
+    /// growing is not zeroed, even if the `RawVec` was created zeroed.
     pub fn resize(&mut self, new_cap: usize) {
-        match self.try_resize(new_cap) {
-            Err(RawVecErr::Alloc(err)) => handle_alloc_error(err.layout),
-            Err(RawVecErr::Layout(err)) => {
-                panic!("Capacity overflows memory size: {}", err)
-            }
-
-            Ok(_) => (),
-        }
+        handle_raw_vec_err(self.try_resize(new_cap))
     }
 
     /// Resizes the `RawVec` with a given capacity. In case of allocation
     /// error or overflow calculating the total size, `Err` is returned. In case
-    /// of failure, the original allocation is untouched.
+    /// of failure, the original allocation is untouched. Coming from a
+    /// zero-sized allocation (capacity `0` or zero-sized `T`), memory is
+    /// allocated fresh; going to one, the memory is freed and a dangling
+    /// pointer is used. Zero-sized types never touch the allocator.
     pub fn try_resize(&mut self, new_cap: usize) -> Result<(), RawVecErr> {
         let layout = Self::make_layout(new_cap)?;
 
-        let res = if layout.size() == 0 {
-            self.free();
-            Ok(NonNull::dangling())
-        } else {
-            let old = Self::make_layout(self.cap).unwrap();
-            NonNull::new(unsafe {
-                realloc(self.nnptr.cast().as_ptr(), old, layout.size())
-            })
-                .map(NonNull::cast::<T>)
-                .ok_or_else(|| AllocErr { layout }.into())
+        let res = match (self.current_layout(), layout.size()) {
+            (None, 0) => Ok(NonNull::dangling()),
+            (None, _) => self.alloc.alloc(layout).map(NonNull::cast::<T>),
+            (Some(_), 0) => {
+                self.free();
+                Ok(NonNull::dangling())
+            }
+            (Some(old), new_size) => unsafe {
+                self.alloc
+                    .realloc(self.nnptr.cast(), old, new_size)
+                    .map(NonNull::cast::<T>)
+            },
         };
 
         res.map(|nnptr| {
             self.nnptr = nnptr;
             self.cap = new_cap;
         })
+        .map_err(Into::into)
+    }
+
+    /// Ensures the `RawVec` has room for at least `additional` elements after
+    /// the first `used` ones, growing it according to the default
+    /// `GrowthPolicy` if needed. In case of allocation error, the handler
+    /// registered via stdlib is called. In case of overflow calculating the
+    /// total size, the function panics.
+    pub fn reserve(&mut self, used: usize, additional: usize) {
+        self.grow_amortized(used, additional, GrowthPolicy::default())
+    }
+
+    /// Ensures the `RawVec` has room for at least `additional` elements after
+    /// the first `used` ones, growing it according to the default
+    /// `GrowthPolicy` if needed. In case of allocation error or overflow
+    /// calculating the total size, `Err` is returned and the original
+    /// allocation is untouched.
+    pub fn try_reserve(
+        &mut self,
+        used: usize,
+        additional: usize,
+    ) -> Result<(), RawVecErr> {
+        self.try_grow_amortized(used, additional, GrowthPolicy::default())
+    }
+
+    /// Ensures the `RawVec` has room for exactly `additional` elements after
+    /// the first `used` ones, growing it to exactly `used + additional` if
+    /// needed. In case of allocation error, the handler registered via stdlib
+    /// is called. In case of overflow calculating the total size, the function
+    /// panics.
+    pub fn reserve_exact(&mut self, used: usize, additional: usize) {
+        handle_raw_vec_err(self.try_reserve_exact(used, additional))
+    }
+
+    /// Ensures the `RawVec` has room for exactly `additional` elements after
+    /// the first `used` ones, growing it to exactly `used + additional` if
+    /// needed. In case of allocation error or overflow calculating the total
+    /// size, `Err` is returned and the original allocation is untouched.
+    pub fn try_reserve_exact(
+        &mut self,
+        used: usize,
+        additional: usize,
+    ) -> Result<(), RawVecErr> {
+        let required = used.checked_add(additional).ok_or(LayoutErr)?;
+        if required > self.cap {
+            self.try_resize(required)?;
+        }
+        Ok(())
+    }
+
+    /// Ensures the `RawVec` has room for at least `additional` elements after
+    /// the first `used` ones. If it has to grow, the new capacity is the
+    /// greatest between `used + additional` and the one given by the passed
+    /// policy. In case of allocation error, the handler registered via stdlib
+    /// is called. In case of overflow calculating the total size, the function
+    /// panics.
+    pub fn grow_amortized(
+        &mut self,
+        used: usize,
+        additional: usize,
+        policy: GrowthPolicy,
+    ) {
+        handle_raw_vec_err(self.try_grow_amortized(used, additional, policy))
+    }
+
+    /// Ensures the `RawVec` has room for at least `additional` elements after
+    /// the first `used` ones. If it has to grow, the new capacity is the
+    /// greatest between `used + additional` and the one given by the passed
+    /// policy. In case of allocation error or overflow calculating the total
+    /// size, `Err` is returned and the original allocation is untouched.
+    pub fn try_grow_amortized(
+        &mut self,
+        used: usize,
+        additional: usize,
+        policy: GrowthPolicy,
+    ) -> Result<(), RawVecErr> {
+        let required = used.checked_add(additional).ok_or(LayoutErr)?;
+        if required <= self.cap {
+            return Ok(());
+        }
+
+        let amortized = policy.next_cap(self.cap).max(required);
+        if Self::make_layout(amortized).is_ok() {
+            self.try_resize(amortized)
+        } else {
+            self.try_resize(required)
+        }
+    }
+
+    /// Shrinks the capacity of the `RawVec` to `min`. If the capacity is
+    /// already less than or equal to `min`, this is a no-op. In case of
+    /// allocation error, the handler registered via stdlib is called.
+    pub fn shrink_to(&mut self, min: usize) {
+        handle_raw_vec_err(self.try_shrink_to(min))
+    }
+
+    /// Shrinks the capacity of the `RawVec` to `min`. If the capacity is
+    /// already less than or equal to `min`, this is a no-op. In case of
+    /// allocation error, `Err` is returned and the original allocation is
+    /// untouched.
+    pub fn try_shrink_to(&mut self, min: usize) -> Result<(), RawVecErr> {
+        if min < self.cap {
+            self.try_resize(min)?;
+        }
+        Ok(())
+    }
+
+    /// The layout of the current allocation, or `None` if the pointer is
+    /// dangling because the allocation is zero-sized.
+    fn current_layout(&self) -> Option<Layout> {
+        let layout = Self::make_layout(self.cap).ok()?;
+        if layout.size() == 0 {
+            None
+        } else {
+            Some(layout)
+        }
     }
 
     fn free(&self) {
-        if self.cap != 0 && mem::size_of::<T>() != 0 {
-            let layout = Self::make_layout(self.cap).unwrap();
+        if let Some(layout) = self.current_layout() {
             unsafe {
-                dealloc(self.nnptr.cast().as_ptr(), layout);
+                self.alloc.dealloc(self.nnptr.cast(), layout);
             }
         }
     }
@@ -239,7 +518,23 @@ impl<T> RawVec<T> {
     }
 }
 
-impl<T> fmt::Debug for RawVec<T> {
+/// Unwraps the result of a fallible `RawVec` operation. In case of allocation
+/// error, the handler registered via stdlib is called. In case of overflow
+/// calculating the total size, it panics.
+pub(crate) fn handle_raw_vec_err<T>(res: Result<T, RawVecErr>) -> T {
+    match res {
+        Ok(val) => val,
+        Err(RawVecErr::Alloc(err)) => handle_alloc_error(err.layout),
+        Err(RawVecErr::Layout(err)) => {
+            panic!("Capacity overflows memory size: {}", err)
+        }
+    }
+}
+
+impl<T, A> fmt::Debug for RawVec<T, A>
+where
+    A: Allocator,
+{
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         write!(
             fmtr,
@@ -249,25 +544,146 @@ impl<T> fmt::Debug for RawVec<T> {
     }
 }
 
-impl<T> Drop for RawVec<T> {
+impl<T, A> Drop for RawVec<T, A>
+where
+    A: Allocator,
+{
     fn drop(&mut self) {
         self.free();
     }
 }
 
-impl<T> From<UninitAlloc<T>> for RawVec<T> {
-    fn from(alloc: UninitAlloc<T>) -> Self {
-        Self { nnptr: alloc.into_raw(), cap: 1, _marker: PhantomData }
+impl<T, A> From<UninitAlloc<T, A>> for RawVec<T, A>
+where
+    A: Allocator,
+{
+    fn from(alloc: UninitAlloc<T, A>) -> Self {
+        let (nnptr, alloc) = alloc.into_raw_with_alloc();
+        Self { nnptr, cap: 1, alloc, _marker: PhantomData }
     }
 }
 
-unsafe impl<T> Send for RawVec<T> where T: Send {}
+unsafe impl<T, A> Send for RawVec<T, A>
+where
+    T: Send,
+    A: Allocator + Send,
+{
+}
 
-unsafe impl<T> Sync for RawVec<T> where T: Sync {}
+unsafe impl<T, A> Sync for RawVec<T, A>
+where
+    T: Sync,
+    A: Allocator + Sync,
+{
+}
 
 #[cfg(test)]
 mod test {
-    use super::RawVec;
+    use super::{
+        super::{
+            AllocStats,
+            CountingAlloc,
+            FailPolicy,
+            FailingAlloc,
+            Global,
+            RawVecErr,
+        },
+        GrowthPolicy,
+        RawVec,
+    };
+    use core::mem;
+
+    fn reallocs(stats: AllocStats) -> usize {
+        stats.reallocs_in_place + stats.reallocs_moved
+    }
+
+    fn check_transitions<T>() {
+        let counter = CountingAlloc::new();
+        let mut alloc = RawVec::<T, _>::new_in(&counter);
+        let size = mem::size_of::<T>();
+
+        for cap in [0, 0, 1, 7, 7, 0, 3, 0, 64, 2, 0, 5] {
+            let before = counter.stats();
+            alloc.resize(cap);
+            let after = counter.stats();
+
+            let was_alloc = before.live_bytes != 0;
+            let is_alloc = cap * size != 0;
+
+            assert_eq!(alloc.cap(), cap);
+            assert_eq!(after.live_bytes, cap * size);
+            assert_eq!(
+                after.allocs - before.allocs,
+                (!was_alloc && is_alloc) as usize
+            );
+            assert_eq!(
+                after.deallocs - before.deallocs,
+                (was_alloc && !is_alloc) as usize
+            );
+            assert_eq!(
+                reallocs(after) - reallocs(before),
+                (was_alloc && is_alloc) as usize
+            );
+            assert_eq!(alloc.raw().as_ptr() as usize % mem::align_of::<T>(), 0);
+        }
+
+        drop(alloc);
+        assert_eq!(counter.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn resize_transitions() {
+        check_transitions::<u8>();
+        check_transitions::<u32>();
+        check_transitions::<[u64; 3]>();
+        check_transitions::<()>();
+        check_transitions::<[u128; 0]>();
+    }
+
+    #[test]
+    fn zero_sized_never_allocates() {
+        let counter = CountingAlloc::new();
+        let mut alloc = RawVec::<(), _>::with_capacity_in(10, &counter);
+        alloc.resize(usize::MAX);
+        assert_eq!(alloc.cap(), usize::MAX);
+        alloc.reserve(usize::MAX, 0);
+        alloc.shrink_to(3);
+        assert_eq!(alloc.cap(), 3);
+        drop(alloc);
+        assert_eq!(counter.stats(), AllocStats::default());
+    }
+
+    #[test]
+    fn huge_caps_leave_allocation_untouched() {
+        let mut alloc = RawVec::<u32>::with_capacity(4);
+        let raw = alloc.raw();
+
+        for cap in [usize::MAX, usize::MAX / 4 + 1, isize::MAX as usize] {
+            match alloc.try_resize(cap) {
+                Err(RawVecErr::Layout(_)) => (),
+                res => panic!("unexpected result {:?}", res),
+            }
+            assert_eq!((alloc.cap(), alloc.raw()), (4, raw));
+        }
+
+        let mut alloc = RawVec::<u32>::new();
+        assert!(alloc.try_resize(usize::MAX).is_err());
+        assert_eq!(alloc.cap(), 0);
+    }
+
+    #[test]
+    fn failed_alloc_leaves_allocation_untouched() {
+        let failing = FailingAlloc::new(FailPolicy::AboveBytes(16));
+
+        let mut alloc = RawVec::<u32, _>::new_in(&failing);
+        assert!(matches!(alloc.try_resize(5), Err(RawVecErr::Alloc(_))));
+        assert_eq!(alloc.cap(), 0);
+
+        alloc.resize(4);
+        let raw = alloc.raw();
+        assert!(matches!(alloc.try_resize(100), Err(RawVecErr::Alloc(_))));
+        assert_eq!((alloc.cap(), alloc.raw()), (4, raw));
+    }
 
     #[test]
     fn cap_is_the_one_passed() {
@@ -288,4 +704,64 @@ mod test {
         let raw = unsafe { RawVec::from_vec(vec) };
         assert_eq!(raw.cap(), 465);
     }
+
+    #[test]
+    fn zeroed() {
+        let alloc = RawVec::<u32>::with_capacity_zeroed(1000);
+        assert!(unsafe { alloc.as_slice() }.iter().all(|&elem| elem == 0));
+    }
+
+    #[test]
+    fn reserve() {
+        let mut alloc = RawVec::<u32>::with_capacity(4);
+        alloc.reserve(4, 0);
+        assert_eq!(alloc.cap(), 4);
+
+        alloc.reserve(4, 1);
+        assert_eq!(alloc.cap(), 8);
+
+        alloc.reserve(8, 20);
+        assert_eq!(alloc.cap(), 28);
+
+        alloc.reserve_exact(28, 1);
+        assert_eq!(alloc.cap(), 29);
+
+        assert!(alloc.try_reserve(usize::MAX, 1).is_err());
+        assert!(alloc.try_reserve_exact(1, usize::MAX / 2).is_err());
+        assert_eq!(alloc.cap(), 29);
+    }
+
+    #[test]
+    fn grow_amortized() {
+        let mut alloc = RawVec::<u8>::with_capacity(10);
+        alloc.grow_amortized(10, 1, GrowthPolicy::OneAndHalf);
+        assert_eq!(alloc.cap(), 15);
+
+        alloc.grow_amortized(15, 1, GrowthPolicy::FixedStep(100));
+        assert_eq!(alloc.cap(), 115);
+
+        alloc.grow_amortized(115, 1000, GrowthPolicy::Double);
+        assert_eq!(alloc.cap(), 1115);
+
+        assert_eq!(GrowthPolicy::Double.next_cap(usize::MAX), usize::MAX);
+    }
+
+    #[test]
+    fn shrink_to() {
+        let mut alloc = RawVec::<u64>::with_capacity(100);
+        alloc.shrink_to(200);
+        assert_eq!(alloc.cap(), 100);
+
+        alloc.shrink_to(30);
+        assert_eq!(alloc.cap(), 30);
+    }
+
+    #[test]
+    fn with_allocator() {
+        let mut alloc = RawVec::<u16, _>::with_capacity_in(10, &Global);
+        assert_eq!(alloc.cap(), 10);
+
+        alloc.resize(30);
+        assert_eq!(alloc.cap(), 30);
+    }
 }
@@ -1,22 +1,30 @@
-use super::{AllocErr, OwnedAlloc, RawVec};
-use std::{
-    alloc::{alloc, dealloc, handle_alloc_error, Layout},
+use super::{AllocErr, Allocator, Global, OwnedAlloc, RawVec, RawVecErr};
+use alloc::{alloc::handle_alloc_error, boxed::Box};
+#[cfg(feature = "nightly")]
+use core::{marker::Unsize, ops::CoerceUnsized};
+use core::{
+    alloc::Layout,
+    convert::Infallible,
     fmt,
     marker::PhantomData,
-    mem,
-    ptr::NonNull,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    pin::Pin,
+    ptr::{self, NonNull},
 };
 
 /// Dynamic allocation of a `T` whose memory is considered uninitialized. The
 /// allocation is freed on `drop`. If the size of the allocation is zero, no
 /// allocation is performed and a dangling pointer is used (just like in `std`).
 /// For the drop checker, the type acts as if it contains a `T` due to usage of
-/// `PhantomData<T>`.
-pub struct UninitAlloc<T>
+/// `PhantomData<T>`. The memory is obtained from the allocator `A`, which is
+/// the global allocator by default.
+pub struct UninitAlloc<T, A = Global>
     where
         T: ?Sized,
+        A: Allocator,
 {
     nnptr: NonNull<T>,
+    alloc: A,
     _marker: PhantomData<T>,
 }
 
@@ -31,62 +39,246 @@ impl<T> UninitAlloc<T> {
     /// Creates room for a `T`. In case of allocation error, the handler
     /// registered via stdlib is called.
     pub fn new() -> Self {
-        Self::try_new().unwrap_or_else(|err| handle_alloc_error(err.layout))
+        Self::new_in(Global)
     }
 
     /// Creates room for a `T`. In case of allocation error, `Err` is returned.
     pub fn try_new() -> Result<Self, AllocErr> {
+        Self::try_new_in(Global)
+    }
+
+    /// Creates room for a `T` whose bytes are all zero. The memory is still
+    /// considered uninitialized. In case of allocation error, the handler
+    /// registered via stdlib is called.
+    pub fn new_zeroed() -> Self {
+        Self::new_zeroed_in(Global)
+    }
+
+    /// Creates room for a `T` whose bytes are all zero. The memory is still
+    /// considered uninitialized. In case of allocation error, `Err` is
+    /// returned.
+    pub fn try_new_zeroed() -> Result<Self, AllocErr> {
+        Self::try_new_zeroed_in(Global)
+    }
+
+    /// Recreate the `UninitAlloc` from a plain old standard library `Box` of
+    /// `MaybeUninit<T>`.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because there are no guarantees that `Box` and
+    /// `UninitAlloc` allocate in the same way. They probably do in the Rust
+    /// version you are using, but there are no future guarantees.
+    pub unsafe fn from_box(boxed: Box<MaybeUninit<T>>) -> Self {
+        let raw = Box::into_raw(boxed) as *mut T;
+        Self::from_raw(NonNull::new_unchecked(raw))
+    }
+
+    /// Converts the allocation into a plain old standard library `Box` of
+    /// `MaybeUninit<T>`.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because there are no guarantees that `Box` and
+    /// `UninitAlloc` allocate in the same way. They probably do in the Rust
+    /// version you are using, but there are no future guarantees.
+    pub unsafe fn into_box(self) -> Box<MaybeUninit<T>> {
+        Box::from_raw(self.into_raw().cast::<MaybeUninit<T>>().as_ptr())
+    }
+}
+
+impl<T, A> UninitAlloc<T, A>
+    where
+        A: Allocator,
+{
+    /// Creates room for a `T` using the given allocator. In case of allocation
+    /// error, the handler registered via stdlib is called.
+    pub fn new_in(alloc: A) -> Self {
+        Self::try_new_in(alloc)
+            .unwrap_or_else(|err| handle_alloc_error(err.layout))
+    }
+
+    /// Creates room for a `T` using the given allocator. In case of allocation
+    /// error, `Err` is returned.
+    pub fn try_new_in(alloc: A) -> Result<Self, AllocErr> {
+        Self::try_new_with(alloc, false)
+    }
+
+    /// Creates room for a `T` whose bytes are all zero using the given
+    /// allocator. The memory is still considered uninitialized. In case of
+    /// allocation error, the handler registered via stdlib is called.
+    pub fn new_zeroed_in(alloc: A) -> Self {
+        Self::try_new_zeroed_in(alloc)
+            .unwrap_or_else(|err| handle_alloc_error(err.layout))
+    }
+
+    /// Creates room for a `T` whose bytes are all zero using the given
+    /// allocator. The memory is still considered uninitialized. In case of
+    /// allocation error, `Err` is returned.
+    pub fn try_new_zeroed_in(alloc: A) -> Result<Self, AllocErr> {
+        Self::try_new_with(alloc, true)
+    }
+
+    fn try_new_with(alloc: A, zeroed: bool) -> Result<Self, AllocErr> {
         let layout = Layout::new::<T>();
 
         let res = if layout.size() == 0 {
             Ok(NonNull::dangling())
+        } else if zeroed {
+            alloc.alloc_zeroed(layout).map(NonNull::cast::<T>)
         } else {
-            NonNull::new(unsafe { alloc(layout) })
-                .map(NonNull::cast::<T>)
-                .ok_or(AllocErr { layout })
+            alloc.alloc(layout).map(NonNull::cast::<T>)
         };
 
-        res.map(|nnptr| Self { nnptr, _marker: PhantomData })
+        res.map(|nnptr| Self { nnptr, alloc, _marker: PhantomData })
+    }
+
+    /// Calls a function with the uninitialized memory as a `MaybeUninit<T>`
+    /// and returns the allocation now considered initialized. The passed
+    /// function must initialize the memory and return the reference to the
+    /// initialized value, such as the one returned by `MaybeUninit::write`.
+    /// If the function panics, the allocation is freed, but whatever it has
+    /// written is not dropped.
+    ///
+    /// # Panics
+    /// Panics if the returned reference does not point to this allocation.
+    /// The allocation is freed, but whatever the function has written is not
+    /// dropped.
+    pub fn init_uninit<F>(mut self, init: F) -> OwnedAlloc<T, A>
+        where
+            F: FnOnce(&mut MaybeUninit<T>) -> &mut T,
+    {
+        let inited = NonNull::from(init(self.as_uninit_mut()));
+        assert!(
+            inited == self.nnptr,
+            "the returned reference does not point to the allocation"
+        );
+        let (raw, alloc) = self.into_raw_with_alloc();
+        unsafe { OwnedAlloc::from_raw_in(raw, alloc) }
+    }
+
+    /// Returns the uninitialized memory as a shared `MaybeUninit<T>`.
+    pub fn as_uninit(&self) -> &MaybeUninit<T> {
+        unsafe { self.nnptr.cast::<MaybeUninit<T>>().as_ref() }
+    }
+
+    /// Returns the uninitialized memory as a mutable `MaybeUninit<T>`, which
+    /// can be initialized without `unsafe`. After that, `assume_init` turns the
+    /// allocation into an `OwnedAlloc`.
+    pub fn as_uninit_mut(&mut self) -> &mut MaybeUninit<T> {
+        unsafe { self.nnptr.cast::<MaybeUninit<T>>().as_mut() }
+    }
+
+    /// Writes the passed value into the memory and returns a mutable reference
+    /// to it. The memory is still considered uninitialized by the allocation,
+    /// so the value is not dropped unless `assume_init` is called afterwards.
+    /// Writing again overwrites the previous value without dropping it.
+    pub fn write(&mut self, val: T) -> &mut T {
+        self.as_uninit_mut().write(val)
     }
 
     /// Initializes the memory and returns the allocation now considered
     /// initialized.
-    pub fn init(self, val: T) -> OwnedAlloc<T> {
-        let raw = self.into_raw();
+    pub fn init(self, val: T) -> OwnedAlloc<T, A> {
+        let (raw, alloc) = self.into_raw_with_alloc();
         unsafe {
             raw.as_ptr().write(val);
-            OwnedAlloc::from_raw(raw)
+            OwnedAlloc::from_raw_in(raw, alloc)
         }
     }
+
+    /// Calls a function with the final address of the value, initializes the
+    /// memory to the value it returns and pins the allocation. Since the value
+    /// is never moved afterwards, it may store pointers derived from the
+    /// address, e.g. to its own fields. The allocator must live for
+    /// `'static`, as in `OwnedAlloc::into_pin`. If the function panics, the
+    /// allocation is freed.
+    pub fn init_pinned<F>(self, init: F) -> Pin<OwnedAlloc<T, A>>
+        where
+            F: FnOnce(NonNull<T>) -> T,
+            A: 'static,
+    {
+        let val = init(self.nnptr);
+        self.init(val).into_pin()
+    }
 }
 
 impl<T> UninitAlloc<T>
     where
         T: ?Sized,
+{
+    /// Recreate the `UninitAlloc` from a raw non-null pointer.
+    ///
+    /// # Safety
+    /// This functions is `unsafe` because passing the wrong pointer leads to
+    /// undefined behaviour.
+    pub unsafe fn from_raw(nnptr: NonNull<T>) -> Self {
+        Self::from_raw_in(nnptr, Global)
+    }
+}
+
+impl<T, A> UninitAlloc<T, A>
+    where
+        T: ?Sized,
+        A: Allocator,
 {
     /// Calls a function with a mutable reference to uninitialized memory and
     /// returns the allocation now considered initialized. The passed function
-    /// is expected to initialize the memory.
+    /// is expected to initialize the memory. If the function panics, the
+    /// allocation is freed, but whatever it has written is not dropped.
     ///
     /// # Safety
     /// This function is `unsafe` because the passed function might not
     /// initialize the memory correctly.
-    pub unsafe fn init_in_place<F>(self, init: F) -> OwnedAlloc<T>
+    pub unsafe fn init_in_place<F>(mut self, init: F) -> OwnedAlloc<T, A>
         where
             F: FnOnce(&mut T),
     {
-        let mut raw = self.into_raw();
-        init(raw.as_mut());
-        OwnedAlloc::from_raw(raw)
+        init(self.nnptr.as_mut());
+        let (raw, alloc) = self.into_raw_with_alloc();
+        OwnedAlloc::from_raw_in(raw, alloc)
     }
 
-    /// Recreate the `UninitAlloc` from a raw non-null pointer.
+    /// Calls a function with a mutable reference to uninitialized memory and
+    /// returns the allocation now considered initialized, pinned. The memory
+    /// is initialized at its final address, so the function may set up
+    /// pointers to it. The allocator must live for `'static`, as in
+    /// `OwnedAlloc::into_pin`. If the function panics, the allocation is freed,
+    /// but whatever it has written is not dropped.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because the passed function might not
+    /// initialize the memory correctly.
+    pub unsafe fn init_pinned_in_place<F>(
+        self,
+        init: F,
+    ) -> Pin<OwnedAlloc<T, A>>
+        where
+            F: FnOnce(&mut T),
+            A: 'static,
+    {
+        self.init_in_place(init).into_pin()
+    }
+
+    /// Recreate the `UninitAlloc` from a raw non-null pointer and the
+    /// allocator which allocated it.
     ///
     /// # Safety
     /// This functions is `unsafe` because passing the wrong pointer leads to
-    /// undefined behaviour.
-    pub unsafe fn from_raw(nnptr: NonNull<T>) -> Self {
-        Self { nnptr, _marker: PhantomData }
+    /// undefined behaviour. Passing an allocator other than the one used to
+    /// allocate the pointer is also undefined behaviour.
+    pub unsafe fn from_raw_in(nnptr: NonNull<T>, alloc: A) -> Self {
+        Self { nnptr, alloc, _marker: PhantomData }
+    }
+
+    /// Returns the allocation now considered initialized, without touching the
+    /// memory. This is meant to be called after the memory has been
+    /// initialized through `as_uninit_mut`, `write` or the slice equivalents.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because calling it before the memory is
+    /// fully initialized leads to undefined behaviour.
+    pub unsafe fn assume_init(self) -> OwnedAlloc<T, A> {
+        let (raw, alloc) = self.into_raw_with_alloc();
+        OwnedAlloc::from_raw_in(raw, alloc)
     }
 
     /// Returns the raw non-null pointer of the allocation.
@@ -94,51 +286,248 @@ impl<T> UninitAlloc<T>
         self.nnptr
     }
 
+    /// Returns a reference to the allocator backing this allocation.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     /// "Forgets" dropping the allocation and returns its raw non-null pointer.
+    /// The allocator is forgotten as well.
     pub fn into_raw(self) -> NonNull<T> {
         let nnptr = self.nnptr;
         mem::forget(self);
         nnptr
     }
+
+    /// "Forgets" dropping the allocation and returns its raw non-null pointer
+    /// together with the allocator which allocated it.
+    pub fn into_raw_with_alloc(self) -> (NonNull<T>, A) {
+        let this = ManuallyDrop::new(self);
+        (this.nnptr, unsafe { ptr::read(&this.alloc) })
+    }
+}
+
+impl<T> UninitAlloc<[T]> {
+    /// Creates room for a slice of `len` elements of type `T`. In case of
+    /// allocation error, the handler registered via stdlib is called. In case
+    /// of overflow calculating the total size, the function panics.
+    pub fn new_slice(len: usize) -> Self {
+        Self::new_slice_in(len, Global)
+    }
+
+    /// Creates room for a slice of `len` elements of type `T`. In case of
+    /// allocation error or overflow calculating the total size, `Err` is
+    /// returned.
+    pub fn try_new_slice(len: usize) -> Result<Self, RawVecErr> {
+        Self::try_new_slice_in(len, Global)
+    }
+
+    /// Recreate the `UninitAlloc` from a plain old standard library boxed
+    /// slice of `MaybeUninit<T>`.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because there are no guarantees that `Box` and
+    /// `UninitAlloc` allocate in the same way. They probably do in the Rust
+    /// version you are using, but there are no future guarantees.
+    pub unsafe fn from_box_slice(boxed: Box<[MaybeUninit<T>]>) -> Self {
+        let raw = Box::into_raw(boxed) as *mut [T];
+        Self::from_raw(NonNull::new_unchecked(raw))
+    }
+
+    /// Converts the allocation into a plain old standard library boxed slice
+    /// of `MaybeUninit<T>`.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because there are no guarantees that `Box` and
+    /// `UninitAlloc` allocate in the same way. They probably do in the Rust
+    /// version you are using, but there are no future guarantees.
+    pub unsafe fn into_box_slice(self) -> Box<[MaybeUninit<T>]> {
+        Box::from_raw(self.into_raw().as_ptr() as *mut [MaybeUninit<T>])
+    }
+}
+
+impl<T, A> UninitAlloc<[T], A>
+    where
+        A: Allocator,
+{
+    /// Creates room for a slice of `len` elements of type `T` using the given
+    /// allocator. In case of allocation error, the handler registered via
+    /// stdlib is called. In case of overflow calculating the total size, the
+    /// function panics.
+    pub fn new_slice_in(len: usize, alloc: A) -> Self {
+        RawVec::with_capacity_in(len, alloc).into()
+    }
+
+    /// Creates room for a slice of `len` elements of type `T` using the given
+    /// allocator. In case of allocation error or overflow calculating the
+    /// total size, `Err` is returned.
+    pub fn try_new_slice_in(len: usize, alloc: A) -> Result<Self, RawVecErr> {
+        RawVec::try_with_capacity_in(len, alloc).map(Into::into)
+    }
+
+    /// The number of elements the slice has room for.
+    pub fn len(&self) -> usize {
+        self.nnptr.len()
+    }
+
+    /// Tests if the slice has room for no element.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the uninitialized memory as a shared slice of `MaybeUninit<T>`.
+    pub fn as_uninit_slice(&self) -> &[MaybeUninit<T>] {
+        let raw = self.nnptr.as_ptr() as *const [MaybeUninit<T>];
+        unsafe { &*raw }
+    }
+
+    /// Returns the uninitialized memory as a mutable slice of `MaybeUninit<T>`,
+    /// whose elements can be initialized without `unsafe`. After that,
+    /// `assume_init` turns the allocation into an `OwnedAlloc`.
+    pub fn as_uninit_slice_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        let raw = self.nnptr.as_ptr() as *mut [MaybeUninit<T>];
+        unsafe { &mut *raw }
+    }
+
+    /// Initializes each element with the return value of the passed function,
+    /// called with the index of the element, in order, and returns the
+    /// allocation now considered initialized. If the function panics, the
+    /// elements already initialized are dropped and the allocation is freed.
+    pub fn init_with<F>(self, mut init: F) -> OwnedAlloc<[T], A>
+        where
+            F: FnMut(usize) -> T,
+    {
+        match self.try_init_with(|i| Ok::<_, Infallible>(init(i))) {
+            Ok(alloc) => alloc,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Initializes each element with the return value of the passed function,
+    /// called with the index of the element, in order, and returns the
+    /// allocation now considered initialized. If the function returns `Err`
+    /// or panics, the elements already initialized are dropped and the
+    /// allocation is freed. The error is then returned.
+    pub fn try_init_with<F, E>(
+        self,
+        mut init: F,
+    ) -> Result<OwnedAlloc<[T], A>, E>
+        where
+            F: FnMut(usize) -> Result<T, E>,
+    {
+        let len = self.len();
+        let mut guard = SliceGuard { start: self.nnptr.cast::<T>(), init: 0 };
+
+        while guard.init < len {
+            let val = init(guard.init)?;
+            unsafe { guard.start.as_ptr().add(guard.init).write(val) };
+            guard.init += 1;
+        }
+
+        mem::forget(guard);
+        let (nnptr, alloc) = self.into_raw_with_alloc();
+        Ok(unsafe { OwnedAlloc::from_raw_in(nnptr, alloc) })
+    }
+}
+
+/// Drops the initialized prefix of a slice being initialized, on early return
+/// or unwind.
+struct SliceGuard<T> {
+    start: NonNull<T>,
+    init: usize,
+}
+
+impl<T> Drop for SliceGuard<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::slice_from_raw_parts_mut(self.start.as_ptr(), self.init)
+                .drop_in_place();
+        }
+    }
 }
 
-impl<T> Drop for UninitAlloc<T>
+impl<A> From<UninitAlloc<str, A>> for UninitAlloc<[u8], A>
+    where
+        A: Allocator,
+{
+    fn from(alloc: UninitAlloc<str, A>) -> Self {
+        let (nnptr, alloc) = alloc.into_raw_with_alloc();
+        let nnptr =
+            unsafe { NonNull::new_unchecked(nnptr.as_ptr() as *mut [u8]) };
+        Self { nnptr, alloc, _marker: PhantomData }
+    }
+}
+
+impl<T, A> Drop for UninitAlloc<T, A>
     where
         T: ?Sized,
+        A: Allocator,
 {
     fn drop(&mut self) {
         unsafe {
             let layout = Layout::for_value(self.nnptr.as_ref());
 
             if layout.size() != 0 {
-                dealloc(self.nnptr.cast().as_ptr(), layout);
+                self.alloc.dealloc(self.nnptr.cast(), layout);
             }
         }
     }
 }
 
-impl<T> fmt::Debug for UninitAlloc<T>
+impl<T, A> fmt::Debug for UninitAlloc<T, A>
     where
         T: ?Sized,
+        A: Allocator,
 {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         write!(fmtr, "{:?}", self.nnptr)
     }
 }
 
-impl<T> From<RawVec<T>> for UninitAlloc<[T]> {
-    fn from(alloc: RawVec<T>) -> Self {
-        Self { nnptr: alloc.into_raw_slice(), _marker: PhantomData }
+impl<T, A> From<RawVec<T, A>> for UninitAlloc<[T], A>
+    where
+        A: Allocator,
+{
+    fn from(alloc: RawVec<T, A>) -> Self {
+        let (nnptr, alloc) = alloc.into_raw_slice_with_alloc();
+        Self { nnptr, alloc, _marker: PhantomData }
     }
 }
 
-unsafe impl<T> Send for UninitAlloc<T> where T: ?Sized + Send {}
+#[cfg(feature = "nightly")]
+impl<T, U, A> CoerceUnsized<UninitAlloc<U, A>> for UninitAlloc<T, A>
+    where
+        T: ?Sized + Unsize<U>,
+        U: ?Sized,
+        A: Allocator,
+{
+}
+
+unsafe impl<T, A> Send for UninitAlloc<T, A>
+    where
+        T: ?Sized + Send,
+        A: Allocator + Send,
+{
+}
 
-unsafe impl<T> Sync for UninitAlloc<T> where T: ?Sized + Sync {}
+unsafe impl<T, A> Sync for UninitAlloc<T, A>
+    where
+        T: ?Sized + Sync,
+        A: Allocator + Sync,
+{
+}
 
 #[cfg(test)]
 mod test {
-    use super::UninitAlloc;
+    extern crate std;
+
+    use super::{super::{CountingAlloc, Global}, UninitAlloc};
+    use alloc::{boxed::Box, rc::Rc};
+    use core::{marker::PhantomPinned, ptr};
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        sync::OnceLock,
+    };
 
     #[test]
     fn into_from_raw() {
@@ -151,4 +540,165 @@ mod test {
         let alloc = unsafe { UninitAlloc::from_raw(raw) };
         assert_eq!(alloc.raw(), raw_borrowed);
     }
+
+    #[test]
+    fn into_from_raw_with_alloc() {
+        let alloc = UninitAlloc::<usize, _>::new_in(&Global);
+        let raw_borrowed = alloc.raw();
+        let (raw, global) = alloc.into_raw_with_alloc();
+
+        assert_eq!(raw, raw_borrowed);
+
+        let alloc = unsafe { UninitAlloc::from_raw_in(raw, global) };
+        assert_eq!(alloc.raw(), raw_borrowed);
+    }
+
+    #[test]
+    fn slice() {
+        let alloc = UninitAlloc::<[u32]>::new_slice(17);
+        assert_eq!(alloc.len(), 17);
+        assert!(UninitAlloc::<[u32]>::new_slice(0).is_empty());
+        assert!(UninitAlloc::<[u32]>::try_new_slice(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn init_in_place_panic_frees() {
+        let counter = CountingAlloc::new();
+        let uninit = UninitAlloc::<[u64; 4], _>::new_in(&counter);
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+            uninit.init_in_place(|_| panic!("init failed"))
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(counter.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn init_uninit() {
+        let alloc = UninitAlloc::new().init_uninit(|slot| slot.write(5u8));
+        assert_eq!(*alloc, 5);
+
+        let counter = CountingAlloc::new();
+        let uninit = UninitAlloc::<u32, _>::new_in(&counter);
+        let mut other = Box::new(3);
+        let other_ptr: *mut u32 = &mut *other;
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            uninit.init_uninit(|_| unsafe { &mut *other_ptr })
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(counter.stats().live_bytes, 0);
+    }
+
+    #[test]
+    fn init_pinned() {
+        struct SelfRef {
+            val: u32,
+            this: *const u32,
+            _pin: PhantomPinned,
+        }
+
+        let uninit = UninitAlloc::<SelfRef>::new();
+        let pinned = uninit.init_pinned(|nnptr| SelfRef {
+            val: 5,
+            this: unsafe { ptr::addr_of!((*nnptr.as_ptr()).val) },
+            _pin: PhantomPinned,
+        });
+        let moved = Box::new(pinned);
+        assert_eq!(moved.this, &moved.val as *const u32);
+        assert_eq!(unsafe { *moved.this }, 5);
+
+        // Pinning needs an allocator living for `'static`.
+        static COUNTER: OnceLock<CountingAlloc> = OnceLock::new();
+        let counter = COUNTER.get_or_init(CountingAlloc::new);
+        let uninit = UninitAlloc::<SelfRef, _>::new_in(counter);
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            uninit.init_pinned(|_| panic!())
+        }));
+        assert!(res.is_err());
+        assert_eq!(counter.stats().live_bytes, 0);
+
+        let pinned = unsafe {
+            UninitAlloc::<[u8; 4]>::new()
+                .init_pinned_in_place(|bytes| *bytes = [1, 2, 3, 4])
+        };
+        assert_eq!(*pinned, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn write_and_assume_init() {
+        let mut uninit = UninitAlloc::<Rc<u8>>::new();
+        *uninit.write(Rc::new(1)) = Rc::new(2);
+        let rc = uninit.as_uninit().as_ptr();
+        let alloc = unsafe { uninit.assume_init() };
+        assert_eq!(&*alloc as *const Rc<u8>, rc);
+        assert_eq!(**alloc, 2);
+
+        let mut uninit = UninitAlloc::<[u16]>::new_slice(3);
+        for (i, slot) in uninit.as_uninit_slice_mut().iter_mut().enumerate() {
+            slot.write(i as u16 * 3);
+        }
+        let alloc = unsafe { uninit.assume_init() };
+        assert_eq!(&*alloc, &[0, 3, 6]);
+    }
+
+    #[test]
+    fn from_into_std_box() {
+        let mut uninit = unsafe { UninitAlloc::from_box(Box::new_uninit()) };
+        uninit.write(7u64);
+        let boxed = unsafe { uninit.into_box().assume_init() };
+        assert_eq!(*boxed, 7);
+
+        let boxed = Box::<[u32]>::new_uninit_slice(4);
+        let uninit = unsafe { UninitAlloc::from_box_slice(boxed) };
+        assert_eq!(uninit.len(), 4);
+        let boxed = unsafe { uninit.into_box_slice() };
+        assert_eq!(boxed.len(), 4);
+    }
+
+    #[test]
+    fn init_with() {
+        let alloc = UninitAlloc::<[usize]>::new_slice(4).init_with(|i| i * 2);
+        assert_eq!(*alloc, [0, 2, 4, 6]);
+    }
+
+    #[test]
+    fn init_with_panic_drops_prefix() {
+        let counter = Rc::new(());
+        let uninit = UninitAlloc::<[Rc<()>]>::new_slice(5);
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            uninit.init_with(|i| {
+                if i == 3 {
+                    panic!("init failed");
+                }
+                counter.clone()
+            })
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn try_init_with_err_drops_prefix() {
+        let counter = Rc::new(());
+        let res = UninitAlloc::<[Rc<()>]>::new_slice(5).try_init_with(|i| {
+            if i == 2 {
+                Err(i)
+            } else {
+                Ok(counter.clone())
+            }
+        });
+
+        assert_eq!(res.unwrap_err(), 2);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn zeroed() {
+        let alloc = UninitAlloc::<[u64; 16]>::new_zeroed();
+        assert_eq!(unsafe { alloc.raw().as_ptr().read() }, [0; 16]);
+    }
 }
@@ -0,0 +1,68 @@
+use core::{
+    cell::{Cell, UnsafeCell},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    num::Wrapping,
+    ptr::NonNull,
+};
+
+/// Types for which a value whose bytes are all zero is valid. Allocations of
+/// such types can be created already initialized with `OwnedAlloc::new_zeroed`,
+/// which lets the allocator (and the operating system) skip writing the zeros.
+///
+/// # Safety
+/// Implementors must guarantee that the all-zero bit pattern is a valid value
+/// of the type. Implementing it for a type such as a reference or `NonNull`
+/// leads to undefined behaviour.
+pub unsafe trait Zeroable {}
+
+macro_rules! impl_zeroable {
+    ($($ty:ty),* $(,)?) => {
+        $(unsafe impl Zeroable for $ty {})*
+    };
+}
+
+impl_zeroable! {
+    (), bool, char, f32, f64,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+}
+
+unsafe impl<T> Zeroable for *const T {}
+
+unsafe impl<T> Zeroable for *mut T {}
+
+unsafe impl<T> Zeroable for Option<NonNull<T>> {}
+
+unsafe impl<'a, T> Zeroable for Option<&'a T> where T: ?Sized + 'a {}
+
+unsafe impl<'a, T> Zeroable for Option<&'a mut T> where T: ?Sized + 'a {}
+
+unsafe impl<T> Zeroable for PhantomData<T> where T: ?Sized {}
+
+unsafe impl<T> Zeroable for MaybeUninit<T> {}
+
+unsafe impl<T> Zeroable for Wrapping<T> where T: Zeroable {}
+
+unsafe impl<T> Zeroable for Cell<T> where T: Zeroable {}
+
+unsafe impl<T> Zeroable for UnsafeCell<T> where T: Zeroable {}
+
+unsafe impl<T, const N: usize> Zeroable for [T; N] where T: Zeroable {}
+
+macro_rules! impl_zeroable_tuple {
+    ($($ty:ident),*) => {
+        unsafe impl<$($ty),*> Zeroable for ($($ty,)*)
+        where
+            $($ty: Zeroable),*
+        {
+        }
+    };
+}
+
+impl_zeroable_tuple!(A);
+impl_zeroable_tuple!(A, B);
+impl_zeroable_tuple!(A, B, C);
+impl_zeroable_tuple!(A, B, C, D);
+impl_zeroable_tuple!(A, B, C, D, E);
+impl_zeroable_tuple!(A, B, C, D, E, F);
@@ -3,12 +3,16 @@
 set -e
 
 test_with_toolchain() {
-    cargo $1 test --target x86_64-unknown-linux-gnu -- --nocapture
-    cargo $1 test --release --target x86_64-unknown-linux-gnu -- --nocapture
+    toolchain=$1
+    shift
+    cargo $toolchain test "$@" --target x86_64-unknown-linux-gnu -- --nocapture
+    cargo $toolchain test "$@" --release --target x86_64-unknown-linux-gnu -- --nocapture
+    cargo $toolchain test "$@" --no-default-features --target x86_64-unknown-linux-gnu -- --nocapture
 }
 
 test_with_toolchain +stable
 test_with_toolchain +nightly
+test_with_toolchain +nightly --features nightly
 
 export RUSTFLAGS='-Z sanitizer=leak'
 export LSAN_OPTIONS='fast_unwind_on_malloc=0'